@@ -0,0 +1,62 @@
+use crate::{
+    cli::{editor::Completer, read_line, run_command},
+    interpreter::interpreter::Interpreter,
+    shell::{ShellState, aliases::with_aliases, completion::CompletionRequest},
+};
+use std::env;
+
+/// Completes with the functions and aliases of the shell and the specifications set by `complete`.
+pub struct ShellCompleter<'a> {
+    pub state: &'a mut ShellState,
+}
+
+impl Completer for ShellCompleter<'_> {
+    fn commands(&self) -> Vec<String> {
+        let aliases = with_aliases(|aliases| aliases.keys().cloned().collect::<Vec<_>>());
+        self.state.functions.keys().cloned().chain(aliases).collect()
+    }
+
+    fn complete(&mut self, request: &CompletionRequest) -> Option<Vec<String>> {
+        let command = request.words.first()?;
+        let spec = self.state.completions.get(command)?.clone();
+
+        let functions = self.commands();
+        let mut candidates = spec.generate(request.current, &functions);
+        if let Some(function) = &spec.function {
+            candidates.extend(self.run_function(function, request));
+        }
+        Some(candidates)
+    }
+}
+
+impl ShellCompleter<'_> {
+    /// Runs a completion function the way bash does: with the command, the word being completed and
+    /// the previous word as arguments, and the `COMP_*` variables describing the line, the words
+    /// being the array `COMP_WORDS`.
+    ///
+    /// The candidates are the elements of `COMPREPLY` when it's an array, as set by `read -a`,
+    /// otherwise its lines, so it can be set to the output of `compgen`.
+    fn run_function(&mut self, function: &str, request: &CompletionRequest) -> Vec<String> {
+        let state = &mut *self.state;
+        state.set_var("COMP_LINE", Some(request.line));
+        state.set_var("COMP_POINT", Some(&request.point.to_string()));
+        state.set_array("COMP_WORDS", request.words.clone());
+        state.set_var("COMP_CWORD", Some(&request.cword.to_string()));
+        state.set_var("COMPREPLY", None);
+
+        let previous = request.cword.checked_sub(1).map_or("", |idx| &request.words[idx]);
+        let args = [request.words[0].clone(), request.current.to_string(), previous.to_string()];
+        let last_status = state.last_status;
+        Interpreter::new(read_line, run_command).run_function(state, function, &args);
+        state.last_status = last_status;
+
+        let reply = match state.arrays.get("COMPREPLY") {
+            Some(array) => array.clone(),
+            None => env::var("COMPREPLY").unwrap_or_default().lines().map(String::from).collect(),
+        };
+        for name in ["COMP_LINE", "COMP_POINT", "COMP_WORDS", "COMP_CWORD", "COMPREPLY"] {
+            state.set_var(name, None);
+        }
+        reply.into_iter().filter(|candidate| !candidate.is_empty()).collect()
+    }
+}
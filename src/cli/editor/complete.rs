@@ -0,0 +1,293 @@
+use std::{collections::BTreeSet, env, mem};
+
+use super::{
+    Line,
+    keys::{Key, read_key},
+    terminal,
+    width::str_width,
+};
+use crate::{
+    cmd::builtin,
+    interpreter::{
+        tokenizer::Tokenizer,
+        tokens::{Operator, Quote, Token},
+    },
+    shell::completion::{self, CompletionRequest},
+};
+
+/// above this many candidates, the user is asked before they are listed
+const LIST_LIMIT: usize = 100;
+
+/// characters separating words, outside of quotes
+const SEPARATORS: &str = ";&|<>()";
+
+/// characters escaped with a backslash when inserted outside of quotes
+const SPECIAL: &str = " \t'\"\\$`!&;|<>()*?[]#~{}";
+
+/// the word being completed
+struct Word {
+    /// the word with its quotes and backslashes removed
+    text: String,
+    /// the quote left open before the cursor
+    quote: Option<char>,
+    /// the word is the name of a command
+    command_position: bool,
+    /// the command the word is an argument of
+    command: Option<String>,
+}
+
+struct Candidate {
+    /// completed word, as matched against the word
+    value: String,
+    /// how it's listed when there are several candidates
+    display: String,
+    /// a directory is completed without the closing space, so its content can follow
+    is_dir: bool,
+}
+
+/// Completion provided by the shell running the editor.
+pub trait Completer {
+    /// names of the functions and aliases, completed as commands
+    fn commands(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Candidates given by the completion specification of the command being completed, `None` without one.
+    fn complete(&mut self, _request: &CompletionRequest) -> Option<Vec<String>> {
+        None
+    }
+}
+
+/// only the default completion
+impl Completer for () {}
+
+impl Candidate {
+    fn new(value: String) -> Self {
+        let is_dir = value.ends_with('/');
+        // files are listed by their name
+        let display = match value.trim_end_matches('/').rfind('/') {
+            Some(idx) if !value.starts_with('$') => value[idx + 1..].to_string(),
+            _ => value.clone(),
+        };
+        Self { value, display, is_dir }
+    }
+}
+
+impl Line<'_> {
+    /// Completes the word before the cursor, or lists the candidates when several of them are possible.
+    pub(super) fn complete(&mut self, completer: &mut dyn Completer) {
+        let word = self.current_word();
+        let candidates = self.candidates(&word, completer);
+        let Some(first) = candidates.first() else {
+            return;
+        };
+
+        let common = candidates.iter().fold(first.value.clone(), |common, candidate| {
+            common_prefix(&common, &candidate.value)
+        });
+        let typed = word.text.chars().count();
+        let suffix: String = common.chars().skip(typed).collect();
+
+        if candidates.len() == 1 {
+            let candidate = &candidates[0];
+            let mut insert = quote(&suffix, word.quote);
+            if !candidate.is_dir {
+                if let Some(quote) = word.quote {
+                    insert.push(quote);
+                }
+                insert.push(' ');
+            }
+            self.insert(&insert.chars().collect::<Vec<_>>());
+        } else if !suffix.is_empty() {
+            self.insert(&quote(&suffix, word.quote).chars().collect::<Vec<_>>());
+        } else {
+            self.list(&candidates);
+        }
+    }
+
+    /// Finds the word before the cursor, following the quoting rules of the shell.
+    fn current_word(&self) -> Word {
+        let mut text = String::new();
+        let mut quote = None;
+        let mut words_before = Vec::new();
+        // whether a command starts after the last separator
+        let mut command_position = true;
+
+        let mut chars = self.chars[..self.cursor].iter().copied();
+        while let Some(ch) = chars.next() {
+            match (quote, ch) {
+                (Some(q), _) if ch == q => quote = None,
+                (Some('"') | None, '\\') => text.extend(chars.next()),
+                (Some(_), _) => text.push(ch),
+                (None, '\'' | '"') => quote = Some(ch),
+                (None, _) if ch.is_whitespace() || SEPARATORS.contains(ch) => {
+                    if !text.is_empty() {
+                        words_before.push(std::mem::take(&mut text));
+                    }
+                    if SEPARATORS.contains(ch) {
+                        // after a redirection comes a file name, otherwise a new command
+                        command_position = !"<>".contains(ch);
+                        words_before.clear();
+                    }
+                }
+                (None, _) => text.push(ch),
+            }
+        }
+
+        Word {
+            text,
+            quote,
+            command_position: command_position && words_before.is_empty(),
+            command: words_before.into_iter().next(),
+        }
+    }
+
+    /// Prints the candidates in columns under the line, the line is then drawn again.
+    fn list(&mut self, candidates: &[Candidate]) {
+        let cursor = self.cursor;
+        self.cursor = self.chars.len();
+        self.refresh();
+        terminal::write("\n");
+
+        if candidates.len() > LIST_LIMIT {
+            terminal::write(&format!("Display all {} possibilities? (y or n)", candidates.len()));
+            let answer = read_key();
+            terminal::write("\n");
+            if !matches!(answer, Ok(Some(Key::Char('y' | 'Y')))) {
+                self.cursor_row = 0;
+                self.cursor = cursor;
+                return;
+            }
+        }
+
+        let width = candidates.iter().map(|c| str_width(&c.display)).max().unwrap_or(0) + 2;
+        let columns = (terminal::columns() / width).max(1);
+        let rows = candidates.len().div_ceil(columns);
+
+        let mut out = String::new();
+        for row in 0..rows {
+            for col in 0..columns {
+                let Some(candidate) = candidates.get(col * rows + row) else {
+                    break;
+                };
+                let padding = width - str_width(&candidate.display);
+                out.push_str(&candidate.display);
+                if col + 1 < columns && (col + 1) * rows + row < candidates.len() {
+                    out.push_str(&" ".repeat(padding));
+                }
+            }
+            out.push('\n');
+        }
+        terminal::write(&out);
+
+        self.cursor_row = 0;
+        self.cursor = cursor;
+    }
+
+    /// the possible completions of `word`, sorted
+    fn candidates(&self, word: &Word, completer: &mut dyn Completer) -> Vec<Candidate> {
+        if let Some(name) = word.text.strip_prefix('$')
+            && word.quote != Some('\'')
+            && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        {
+            let names: BTreeSet<String> = env::vars_os()
+                .filter_map(|(name, _)| name.into_string().ok())
+                .filter(|var| var.starts_with(name))
+                .collect();
+            return names.into_iter().map(|name| Candidate::new(format!("${name}"))).collect();
+        }
+
+        if word.command_position && !word.text.contains('/') {
+            let functions = completer.commands();
+            return completion::commands(&word.text, &functions).into_iter().map(Candidate::new).collect();
+        }
+
+        if !word.command_position {
+            let (words, cword) = self.command_words();
+            let line = self.text();
+            let request = CompletionRequest {
+                point: self.chars[..self.cursor].iter().map(|ch| ch.len_utf8()).sum(),
+                line: &line,
+                words,
+                cword,
+                current: &word.text,
+            };
+            if let Some(candidates) = completer.complete(&request) {
+                return candidates.into_iter().map(Candidate::new).collect();
+            }
+        }
+
+        if word.text.starts_with('-')
+            && let Some(command) = &word.command
+            && let Some(builtin) = builtin::get(command)
+        {
+            // the options of builtins are completed after a `-`
+            return builtin
+                .options()
+                .iter()
+                .filter(|option| option.starts_with(&word.text))
+                .map(|option| Candidate::new(option.to_string()))
+                .collect();
+        }
+
+        completion::paths(&word.text, false).into_iter().map(Candidate::new).collect()
+    }
+
+    /// Splits the command before the cursor into words with the shell tokenizer, quotes being kept,
+    /// the last word is the one being completed.
+    fn command_words(&self) -> (Vec<String>, usize) {
+        let source: String = self.chars[..self.cursor].iter().collect();
+        let mut words = Vec::new();
+        let mut word = String::new();
+        let mut quote = None;
+        let mut escaped = false;
+
+        for token in Tokenizer::new(&source) {
+            match token {
+                _ if escaped => escaped = false,
+                Token::BackSlash if quote != Some(Quote::Single) => escaped = true,
+                Token::Quote(q) if quote.is_none() => quote = Some(q),
+                Token::Quote(q) if quote == Some(q) => quote = None,
+                Token::WhiteSpace(_) | Token::Operator(Operator::Redirection(_)) if quote.is_none() => {
+                    if !word.is_empty() {
+                        words.push(mem::take(&mut word));
+                    }
+                    continue;
+                }
+                // a new command starts
+                Token::Operator(_) if quote.is_none() => {
+                    words.clear();
+                    word.clear();
+                    continue;
+                }
+                _ => {}
+            }
+            word.push_str(&String::from(token));
+        }
+
+        words.push(word);
+        let cword = words.len() - 1;
+        (words, cword)
+    }
+}
+
+fn common_prefix(a: &str, b: &str) -> String {
+    a.chars().zip(b.chars()).take_while(|(x, y)| x == y).map(|(x, _)| x).collect()
+}
+
+/// escapes `text` so it's read back as is, inside the quote left open or outside of quotes
+fn quote(text: &str, open_quote: Option<char>) -> String {
+    let mut quoted = String::new();
+    for ch in text.chars() {
+        match open_quote {
+            None if SPECIAL.contains(ch) => quoted.push('\\'),
+            Some('"') if "\"\\$`".contains(ch) => quoted.push('\\'),
+            Some('\'') if ch == '\'' => {
+                quoted.push_str("'\\'");
+            }
+            _ => {}
+        }
+        quoted.push(ch);
+    }
+    quoted
+}
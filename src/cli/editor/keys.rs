@@ -0,0 +1,135 @@
+use std::io::{self, ErrorKind};
+
+use super::terminal::read_byte;
+
+/// how long to wait for the rest of an escape sequence before treating ESC as a key
+const ESCAPE_TIMEOUT: i32 = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Key {
+    Char(char),
+    /// a control character, given as its lowercase letter (`Ctrl('a')` for 0x01)
+    Ctrl(char),
+    /// a character typed with Alt (or after ESC)
+    Alt(char),
+    AltBackspace,
+    Left,
+    Right,
+    Up,
+    Down,
+    CtrlLeft,
+    CtrlRight,
+    Home,
+    End,
+    Delete,
+    Backspace,
+    Enter,
+    Tab,
+    Escape,
+    Unknown,
+}
+
+/// Reads and decodes the next key, `Ok(None)` at the end of input.
+pub fn read_key() -> io::Result<Option<Key>> {
+    let Some(byte) = read_byte(None)? else {
+        return Ok(None);
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7F | 0x08 => Key::Backspace,
+        0x1B => read_escape()?,
+        0x01..=0x1A => Key::Ctrl((b'a' + byte - 1) as char),
+        0x00..=0x1F => Key::Unknown,
+        _ => Key::Char(read_utf8(byte)?),
+    };
+
+    Ok(Some(key))
+}
+
+/// decodes the rest of a UTF-8 sequence starting with `first`, invalid sequences give U+FFFD
+fn read_utf8(first: u8) -> io::Result<char> {
+    let len = match first {
+        0x00..=0x7F => return Ok(first as char),
+        0xC0..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF7 => 4,
+        _ => return Ok(char::REPLACEMENT_CHARACTER),
+    };
+
+    let mut bytes = vec![first];
+    while bytes.len() < len {
+        match read_byte(Some(ESCAPE_TIMEOUT))? {
+            Some(byte) if byte & 0xC0 == 0x80 => bytes.push(byte),
+            _ => return Ok(char::REPLACEMENT_CHARACTER),
+        }
+    }
+
+    Ok(std::str::from_utf8(&bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+        .unwrap_or(char::REPLACEMENT_CHARACTER))
+}
+
+/// decodes what follows ESC: a CSI (`ESC [`) or SS3 (`ESC O`) sequence, or an Alt modified key
+fn read_escape() -> io::Result<Key> {
+    let next = match read_byte(Some(ESCAPE_TIMEOUT)) {
+        Ok(next) => next,
+        Err(e) if e.kind() == ErrorKind::Interrupted => None,
+        Err(e) => return Err(e),
+    };
+
+    let Some(next) = next else {
+        return Ok(Key::Escape);
+    };
+
+    match next {
+        b'[' => read_csi(),
+        b'O' => Ok(match read_byte(Some(ESCAPE_TIMEOUT))? {
+            Some(b'A') => Key::Up,
+            Some(b'B') => Key::Down,
+            Some(b'C') => Key::Right,
+            Some(b'D') => Key::Left,
+            Some(b'H') => Key::Home,
+            Some(b'F') => Key::End,
+            _ => Key::Unknown,
+        }),
+        0x7F | 0x08 => Ok(Key::AltBackspace),
+        0x1B => Ok(Key::Escape),
+        byte if byte.is_ascii_graphic() => Ok(Key::Alt(byte as char)),
+        _ => Ok(Key::Unknown),
+    }
+}
+
+/// decodes `ESC [ parameters final`, like `ESC [ 1 ; 5 D` for Ctrl-Left
+fn read_csi() -> io::Result<Key> {
+    let mut params = String::new();
+    let last = loop {
+        let Some(byte) = read_byte(Some(ESCAPE_TIMEOUT))? else {
+            return Ok(Key::Unknown);
+        };
+        if (0x40..=0x7E).contains(&byte) {
+            break byte;
+        }
+        params.push(byte as char);
+    };
+
+    let ctrl = params.ends_with(";5");
+    let key = match (last, params.as_str()) {
+        (b'A', _) => Key::Up,
+        (b'B', _) => Key::Down,
+        (b'C', _) if ctrl => Key::CtrlRight,
+        (b'D', _) if ctrl => Key::CtrlLeft,
+        (b'C', _) => Key::Right,
+        (b'D', _) => Key::Left,
+        (b'H', _) => Key::Home,
+        (b'F', _) => Key::End,
+        (b'~', "1" | "7") => Key::Home,
+        (b'~', "4" | "8") => Key::End,
+        (b'~', "3") => Key::Delete,
+        _ => Key::Unknown,
+    };
+
+    Ok(key)
+}
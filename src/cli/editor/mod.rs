@@ -0,0 +1,403 @@
+mod complete;
+pub mod keys;
+mod search;
+pub mod terminal;
+pub mod width;
+
+use std::{
+    cell::RefCell,
+    io::{self, ErrorKind},
+};
+
+use crate::{
+    cli::prompt::{END_IGNORE, START_IGNORE},
+    shell::history::with_history,
+};
+pub use complete::Completer;
+use keys::{Key, read_key};
+use terminal::RawMode;
+use width::char_width;
+
+/// how many killed texts are remembered for yanking
+const KILL_RING_SIZE: usize = 16;
+
+thread_local! {
+    static EDITOR: RefCell<LineEditor> = RefCell::new(LineEditor::default());
+}
+
+/// Reads a line with the editor shared by every prompt of the shell, so the kill ring persists,
+/// Up and Down go through the history of the shell and Tab completes with `completer`.
+///
+/// Returns `None` when Ctrl-D is pressed on an empty line, Ctrl-C is reported as an `Interrupted` error.
+pub fn read_line(prompt: &str, completer: &mut dyn Completer) -> io::Result<Option<String>> {
+    let history = with_history(|history| history.entries().to_vec());
+    EDITOR.with_borrow_mut(|editor| editor.read_line(prompt, &history, completer))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Action {
+    Other,
+    KillForward,
+    KillBackward,
+    Yank,
+}
+
+/// Emacs-like line editor working on a terminal in raw mode.
+#[derive(Default)]
+pub struct LineEditor {
+    kill_ring: Vec<String>,
+    /// position in the kill ring of the last yanked text, rotated by Alt-Y
+    yank_index: usize,
+    /// text of the last incremental search, searched again by Ctrl-R on an empty search
+    last_search: String,
+}
+
+/// the line being edited
+struct Line<'a> {
+    prompt: &'a str,
+    chars: Vec<char>,
+    /// index in `chars` of the character under the cursor
+    cursor: usize,
+    /// rows between the first row of the prompt and the cursor, as last drawn
+    cursor_row: usize,
+    last_action: Action,
+    /// range of `chars` inserted by the last yank
+    yanked: (usize, usize),
+    /// index of the history entry shown, the length of the history for the new line
+    history_idx: usize,
+    /// the new line, kept while going through the history
+    new_line: Vec<char>,
+}
+
+impl LineEditor {
+    pub fn read_line(
+        &mut self,
+        prompt: &str,
+        history: &[String],
+        completer: &mut dyn Completer,
+    ) -> io::Result<Option<String>> {
+        let _raw = RawMode::enable()?;
+        let mut line = Line {
+            prompt,
+            chars: Vec::new(),
+            cursor: 0,
+            cursor_row: 0,
+            last_action: Action::Other,
+            yanked: (0, 0),
+            history_idx: history.len(),
+            new_line: Vec::new(),
+        };
+        line.refresh();
+
+        loop {
+            let mut key = match read_key() {
+                Ok(Some(key)) => key,
+                Ok(None) if line.chars.is_empty() => return Ok(None),
+                Ok(None) => Key::Enter,
+                // signals are handled once the line has been read
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if let Key::Ctrl(ch @ ('r' | 's')) = key {
+                // the history file also has the commands saved by other shells
+                let entries = with_history(|history| history.persisted());
+                match self.search(&mut line, &entries, ch == 'r')? {
+                    Some(next) => key = next,
+                    None => {
+                        line.refresh();
+                        continue;
+                    }
+                }
+                line.history_idx = history.len();
+            }
+
+            let last_action = line.last_action;
+            line.last_action = Action::Other;
+
+            match key {
+                Key::Enter => {
+                    line.cursor = line.chars.len();
+                    line.refresh();
+                    terminal::write("\n");
+                    return Ok(Some(line.text() + "\n"));
+                }
+                Key::Ctrl('c') => {
+                    line.cursor = line.chars.len();
+                    line.refresh();
+                    terminal::write("^C");
+                    return Err(io::Error::from(ErrorKind::Interrupted));
+                }
+                Key::Ctrl('d') if line.chars.is_empty() => return Ok(None),
+                Key::Ctrl('d') | Key::Delete if line.cursor < line.chars.len() => {
+                    line.chars.remove(line.cursor);
+                }
+                Key::Backspace if line.cursor > 0 => {
+                    line.cursor -= 1;
+                    line.chars.remove(line.cursor);
+                }
+                Key::Ctrl('a') | Key::Home => line.cursor = 0,
+                Key::Ctrl('e') | Key::End => line.cursor = line.chars.len(),
+                Key::Ctrl('b') | Key::Left => line.cursor = line.cursor.saturating_sub(1),
+                Key::Ctrl('f') | Key::Right => line.cursor = (line.cursor + 1).min(line.chars.len()),
+                Key::Alt('b') | Key::CtrlLeft => line.cursor = line.word_start(),
+                Key::Alt('f') | Key::CtrlRight => line.cursor = line.word_end(),
+                Key::Ctrl('k') => {
+                    let range = (line.cursor, line.chars.len());
+                    self.kill(&mut line, range, last_action, Action::KillForward);
+                }
+                Key::Ctrl('u') => {
+                    let range = (0, line.cursor);
+                    self.kill(&mut line, range, last_action, Action::KillBackward);
+                }
+                Key::Ctrl('w') => {
+                    let range = (line.blank_word_start(), line.cursor);
+                    self.kill(&mut line, range, last_action, Action::KillBackward);
+                }
+                Key::Alt('d') => {
+                    let range = (line.cursor, line.word_end());
+                    self.kill(&mut line, range, last_action, Action::KillForward);
+                }
+                Key::AltBackspace => {
+                    let range = (line.word_start(), line.cursor);
+                    self.kill(&mut line, range, last_action, Action::KillBackward);
+                }
+                Key::Ctrl('p') | Key::Up if line.history_idx > 0 => {
+                    if line.history_idx == history.len() {
+                        line.new_line = line.chars.clone();
+                    }
+                    line.history_idx -= 1;
+                    line.set_text(history[line.history_idx].chars().collect());
+                }
+                Key::Ctrl('n') | Key::Down if line.history_idx < history.len() => {
+                    line.history_idx += 1;
+                    let text = match history.get(line.history_idx) {
+                        Some(entry) => entry.chars().collect(),
+                        None => std::mem::take(&mut line.new_line),
+                    };
+                    line.set_text(text);
+                }
+                Key::Ctrl('y') => self.yank(&mut line),
+                Key::Alt('y') if last_action == Action::Yank => self.yank_pop(&mut line),
+                Key::Ctrl('t') => line.transpose(),
+                Key::Ctrl('l') => {
+                    terminal::write("\x1b[H\x1b[2J");
+                    line.cursor_row = 0;
+                }
+                Key::Tab => line.complete(completer),
+                Key::Char(ch) => line.insert(&[ch]),
+                _ => {}
+            }
+
+            line.refresh();
+        }
+    }
+
+    /// Removes `start..end` from the line and saves it in the kill ring,
+    /// consecutive kills are accumulated in the same entry.
+    fn kill(&mut self, line: &mut Line, (start, end): (usize, usize), last_action: Action, action: Action) {
+        if start >= end {
+            line.last_action = last_action;
+            return;
+        }
+
+        let text: String = line.chars.drain(start..end).collect();
+        line.cursor = start;
+        line.last_action = action;
+
+        let accumulate = matches!(last_action, Action::KillForward | Action::KillBackward);
+        match self.kill_ring.last_mut() {
+            Some(last) if accumulate && action == Action::KillForward => last.push_str(&text),
+            Some(last) if accumulate => last.insert_str(0, &text),
+            _ => {
+                self.kill_ring.push(text);
+                if self.kill_ring.len() > KILL_RING_SIZE {
+                    self.kill_ring.remove(0);
+                }
+            }
+        }
+    }
+
+    /// inserts the most recently killed text
+    fn yank(&mut self, line: &mut Line) {
+        let Some(text) = self.kill_ring.last() else {
+            return;
+        };
+
+        self.yank_index = self.kill_ring.len() - 1;
+        let chars: Vec<char> = text.chars().collect();
+        line.yanked = (line.cursor, line.cursor + chars.len());
+        line.insert(&chars);
+        line.last_action = Action::Yank;
+    }
+
+    /// replaces the text just yanked with the previous entry of the kill ring
+    fn yank_pop(&mut self, line: &mut Line) {
+        if self.kill_ring.is_empty() {
+            return;
+        }
+
+        let (start, end) = line.yanked;
+        line.chars.drain(start..end);
+        line.cursor = start;
+
+        self.yank_index = (self.yank_index + self.kill_ring.len() - 1) % self.kill_ring.len();
+        let chars: Vec<char> = self.kill_ring[self.yank_index].chars().collect();
+        line.yanked = (start, start + chars.len());
+        line.insert(&chars);
+        line.last_action = Action::Yank;
+    }
+}
+
+impl Line<'_> {
+    fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    /// replaces the whole line, leaving the cursor at its end
+    fn set_text(&mut self, chars: Vec<char>) {
+        self.chars = chars;
+        self.cursor = self.chars.len();
+    }
+
+    fn insert(&mut self, chars: &[char]) {
+        self.chars.splice(self.cursor..self.cursor, chars.iter().copied());
+        self.cursor += chars.len();
+    }
+
+    fn is_word_char(&self, idx: usize) -> bool {
+        self.chars[idx].is_alphanumeric()
+    }
+
+    /// start of the word before the cursor, words are made of alphanumeric characters
+    fn word_start(&self) -> usize {
+        let mut idx = self.cursor;
+        while idx > 0 && !self.is_word_char(idx - 1) {
+            idx -= 1;
+        }
+        while idx > 0 && self.is_word_char(idx - 1) {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// end of the word after the cursor
+    fn word_end(&self) -> usize {
+        let mut idx = self.cursor;
+        while idx < self.chars.len() && !self.is_word_char(idx) {
+            idx += 1;
+        }
+        while idx < self.chars.len() && self.is_word_char(idx) {
+            idx += 1;
+        }
+        idx
+    }
+
+    /// start of the white space delimited word before the cursor, as removed by Ctrl-W
+    fn blank_word_start(&self) -> usize {
+        let mut idx = self.cursor;
+        while idx > 0 && self.chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        while idx > 0 && !self.chars[idx - 1].is_whitespace() {
+            idx -= 1;
+        }
+        idx
+    }
+
+    /// swaps the character before the cursor with the one under it
+    fn transpose(&mut self) {
+        if self.cursor == 0 || self.chars.len() < 2 {
+            return;
+        }
+        if self.cursor == self.chars.len() {
+            self.cursor -= 1;
+        }
+        self.chars.swap(self.cursor - 1, self.cursor);
+        self.cursor += 1;
+    }
+
+    fn refresh(&mut self) {
+        let prompt = self.prompt;
+        self.draw(prompt, None);
+    }
+
+    /// Redraws `prompt` and the line with the `highlight` range in reverse video, then puts the cursor
+    /// at its place, lines longer than the terminal wrap on several rows.
+    fn draw(&mut self, prompt: &str, highlight: Option<(usize, usize)>) {
+        let columns = terminal::columns();
+        let mut out = String::new();
+
+        if self.cursor_row > 0 {
+            out.push_str(&format!("\x1b[{}A", self.cursor_row));
+        }
+        out.push_str("\r\x1b[J");
+        out.push_str(&prompt.replace([START_IGNORE, END_IGNORE], ""));
+        for (i, ch) in self.chars.iter().enumerate() {
+            match highlight {
+                Some((start, _)) if i == start => out.push_str("\x1b[7m"),
+                Some((_, end)) if i == end => out.push_str("\x1b[27m"),
+                _ => {}
+            }
+            out.push(*ch);
+        }
+        if highlight.is_some_and(|(_, end)| end >= self.chars.len()) {
+            out.push_str("\x1b[27m");
+        }
+
+        let start = visible_chars(prompt).fold((0, 0), |pos, ch| advance(pos, ch, columns));
+        let (mut end_row, end_col) = self.chars.iter().fold(start, |pos, &ch| advance(pos, ch, columns));
+
+        // the terminal only wraps when the next character is written
+        if end_col == columns {
+            out.push('\n');
+            end_row += 1;
+        }
+
+        let (mut row, mut col) = self.chars[..self.cursor]
+            .iter()
+            .fold(start, |pos, &ch| advance(pos, ch, columns));
+        let next_width = self.chars.get(self.cursor).map_or(1, |&ch| char_width(ch));
+        if col + next_width > columns {
+            row += 1;
+            col = 0;
+        }
+
+        if end_row > row {
+            out.push_str(&format!("\x1b[{}A", end_row - row));
+        }
+        out.push('\r');
+        if col > 0 {
+            out.push_str(&format!("\x1b[{col}C"));
+        }
+
+        self.cursor_row = row;
+        terminal::write(&out);
+    }
+}
+
+/// the characters of `prompt` moving the cursor, leaving out those between `\[` and `\]`
+fn visible_chars(prompt: &str) -> impl Iterator<Item = char> + '_ {
+    let mut ignoring = false;
+    prompt.chars().filter(move |&ch| {
+        match ch {
+            START_IGNORE => ignoring = true,
+            END_IGNORE => ignoring = false,
+            _ => return !ignoring,
+        }
+        false
+    })
+}
+
+/// the position of the cursor after writing `ch` at `(row, col)` on a terminal `columns` wide
+fn advance((row, col): (usize, usize), ch: char, columns: usize) -> (usize, usize) {
+    if ch == '\n' {
+        return (row + 1, 0);
+    }
+
+    let width = char_width(ch);
+    if col + width > columns {
+        return (row + 1, width);
+    }
+    (row, col + width)
+}
@@ -0,0 +1,105 @@
+use std::io::{self, ErrorKind};
+
+use super::{
+    Line, LineEditor,
+    keys::{Key, read_key},
+};
+
+impl LineEditor {
+    /// Incremental search through `entries`, started by Ctrl-R (`backward`) or Ctrl-S.
+    ///
+    /// Ctrl-R and Ctrl-S go to the next match, Ctrl-G restores the line and Esc keeps the match for editing,
+    /// any other key ends the search and is returned to be handled by the editor.
+    pub(super) fn search(&mut self, line: &mut Line, entries: &[String], mut backward: bool) -> io::Result<Option<Key>> {
+        let original = (line.chars.clone(), line.cursor);
+        let mut query = String::new();
+        // index of the entry matched and position of the match in it
+        let mut found: Option<(usize, usize)> = None;
+        let mut failed = false;
+
+        loop {
+            let prompt = format!(
+                "({}{}i-search)`{query}': ",
+                if failed { "failed " } else { "" },
+                if backward { "reverse-" } else { "" }
+            );
+            let highlight = match found {
+                Some((_, pos)) if !query.is_empty() => Some((pos, pos + query.chars().count())),
+                _ => None,
+            };
+            line.draw(&prompt, highlight);
+
+            let key = match read_key() {
+                Ok(Some(key)) => key,
+                Ok(None) => return Ok(Some(Key::Enter)),
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            // where to look for the next match, the entry shown included or not
+            let current = found.map_or(entries.len(), |(idx, _)| idx);
+            let start = match key {
+                Key::Ctrl('r') | Key::Ctrl('s') => {
+                    backward = key == Key::Ctrl('r');
+                    if query.is_empty() {
+                        query = self.last_search.clone();
+                        Some(current)
+                    } else if backward {
+                        current.checked_sub(1)
+                    } else {
+                        Some(current + 1)
+                    }
+                }
+                Key::Char(ch) => {
+                    query.push(ch);
+                    Some(current)
+                }
+                Key::Backspace => {
+                    query.pop();
+                    found = None;
+                    line.chars = original.0.clone();
+                    line.cursor = original.1;
+                    Some(entries.len())
+                }
+                Key::Ctrl('g') => {
+                    line.chars = original.0;
+                    line.cursor = original.1;
+                    return Ok(None);
+                }
+                Key::Escape => return Ok(None),
+                _ => return Ok(Some(key)),
+            };
+
+            self.last_search.clone_from(&query);
+            failed = false;
+            if query.is_empty() {
+                continue;
+            }
+
+            match start.and_then(|start| find(entries, &query, start, backward)) {
+                Some((idx, pos)) => {
+                    found = Some((idx, pos));
+                    line.chars = entries[idx].chars().collect();
+                    line.cursor = pos;
+                }
+                None => failed = true,
+            }
+        }
+    }
+}
+
+/// Finds the first entry containing `query` from `start` included in the direction of the search,
+/// returns its index and the position of the match in characters.
+fn find(entries: &[String], query: &str, start: usize, backward: bool) -> Option<(usize, usize)> {
+    let found = |idx: usize| {
+        let entry = &entries[idx];
+        let byte = if backward { entry.rfind(query) } else { entry.find(query) }?;
+        Some((idx, entry[..byte].chars().count()))
+    };
+
+    if backward {
+        (0..(start + 1).min(entries.len())).rev().find_map(found)
+    } else {
+        (start..entries.len()).find_map(found)
+    }
+}
@@ -0,0 +1,93 @@
+use libc::{
+    BRKINT, ECHO, ICANON, ICRNL, IEXTEN, INPCK, ISIG, ISTRIP, IXON, POLLIN, STDIN_FILENO, STDOUT_FILENO,
+    TCSADRAIN, TIOCGWINSZ, VMIN, VTIME, c_void, ioctl, poll, pollfd, tcgetattr, tcsetattr, termios, winsize,
+};
+use std::{
+    io::{self, Write, stdout},
+    mem::MaybeUninit,
+};
+
+/// Puts the terminal in raw mode for as long as it lives, the previous mode is restored on drop.
+pub struct RawMode {
+    original: termios,
+}
+
+impl RawMode {
+    pub fn enable() -> io::Result<Self> {
+        Self::set(|raw| {
+            // keys are read one at a time, without echo, and Ctrl-C/Ctrl-Z arrive as bytes
+            raw.c_iflag &= !(BRKINT | ICRNL | INPCK | ISTRIP | IXON);
+            raw.c_lflag &= !(ECHO | ICANON | IEXTEN | ISIG);
+            raw.c_cc[VMIN] = 1;
+            raw.c_cc[VTIME] = 0;
+        })
+    }
+
+    /// Changes the mode of the terminal with `change` until the value returned is dropped.
+    pub fn set(change: impl FnOnce(&mut termios)) -> io::Result<Self> {
+        let original = unsafe {
+            let mut original = MaybeUninit::<termios>::uninit();
+            if tcgetattr(STDIN_FILENO, original.as_mut_ptr()) == -1 {
+                return Err(io::Error::last_os_error());
+            }
+            original.assume_init()
+        };
+
+        let mut raw = original;
+        change(&mut raw);
+
+        if unsafe { tcsetattr(STDIN_FILENO, TCSADRAIN, &raw) } == -1 {
+            return Err(io::Error::last_os_error());
+        }
+
+        Ok(Self { original })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        unsafe { tcsetattr(STDIN_FILENO, TCSADRAIN, &self.original) };
+    }
+}
+
+/// the number of columns of the terminal, 80 if it can't be known
+pub fn columns() -> usize {
+    let mut size = MaybeUninit::<winsize>::zeroed();
+    let res = unsafe { ioctl(STDOUT_FILENO, TIOCGWINSZ, size.as_mut_ptr()) };
+    let size = unsafe { size.assume_init() };
+    if res == -1 || size.ws_col == 0 {
+        return 80;
+    }
+    size.ws_col as usize
+}
+
+/// Reads a single byte from standard input, waiting at most `timeout` milliseconds when given.
+///
+/// Returns `Ok(None)` at the end of input or when the timeout expired.
+pub fn read_byte(timeout: Option<i32>) -> io::Result<Option<u8>> {
+    if let Some(timeout) = timeout {
+        let mut fds = pollfd {
+            fd: STDIN_FILENO,
+            events: POLLIN,
+            revents: 0,
+        };
+        match unsafe { poll(&mut fds, 1, timeout) } {
+            -1 => return Err(io::Error::last_os_error()),
+            0 => return Ok(None),
+            _ => {}
+        }
+    }
+
+    let mut byte = 0u8;
+    match unsafe { libc::read(STDIN_FILENO, &mut byte as *mut u8 as *mut c_void, 1) } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => Ok(None),
+        _ => Ok(Some(byte)),
+    }
+}
+
+pub fn write(s: &str) {
+    let mut out = stdout();
+    let _ = out.write_all(s.as_bytes());
+    let _ = out.flush();
+}
@@ -1,6 +1,10 @@
+pub mod completer;
+pub mod editor;
 pub mod print;
+pub mod prompt;
 pub mod read_input;
 pub mod run_command;
+pub mod startup;
 
 pub use print::*;
 pub use read_input::*;
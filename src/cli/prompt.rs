@@ -0,0 +1,195 @@
+use std::{cell::RefCell, env, ffi::CStr};
+
+use chrono::Local;
+use libc::{c_char, geteuid, gethostname};
+
+use crate::{
+    cmd::git_prompt,
+    interpreter::{command::Command, interpreter::Interpreter},
+    shell::{ShellState, history::with_history},
+};
+
+/// starts a sequence of characters that don't move the cursor, written `\[` in prompts
+pub const START_IGNORE: char = '\x01';
+/// ends a sequence started by [`START_IGNORE`], written `\]` in prompts
+pub const END_IGNORE: char = '\x02';
+
+/// marks where the value of an escape goes in a prompt being expanded, followed by its index
+const ESCAPE_MARK: char = '\u{e000}';
+/// the index of the first escape, as a character following [`ESCAPE_MARK`]
+const FIRST_INDEX: u32 = 0xe100;
+
+thread_local! {
+    /// `PS2` as rendered before the command being read, see [`continuation`]
+    static CONTINUATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Builds the prompt given by the variable `var`, or `default` when it's unset:
+/// parameters and command substitutions are expanded, and escapes are decoded into text
+/// that is taken as it is.
+pub fn render<R, E>(inter: &Interpreter<R, E>, state: &mut ShellState, var: &str, default: &str) -> String
+where
+    R: Fn() -> Option<String>,
+    E: Fn(&mut ShellState, Vec<Command>) -> i32,
+{
+    let prompt = env::var(var).unwrap_or_else(|_| default.to_string());
+    let mut marked = String::new();
+    let mut values = Vec::new();
+    decode_with(&prompt, Some(state), |text, escape| {
+        let index = char::from_u32(FIRST_INDEX + values.len() as u32);
+        match index {
+            Some(index) if escape => {
+                marked.push(ESCAPE_MARK);
+                marked.push(index);
+                values.push(text.to_string());
+            }
+            _ => marked.push_str(text),
+        }
+    });
+
+    // showing the prompt doesn't change `$?`
+    let last_status = state.last_status;
+    let expanded = inter.expand(state, &marked);
+    state.last_status = last_status;
+
+    let mut prompt = String::new();
+    let mut chars = expanded.chars();
+    while let Some(ch) = chars.next() {
+        if ch != ESCAPE_MARK {
+            prompt.push(ch);
+            continue;
+        }
+        let value = chars.next().and_then(|index| values.get((index as u32).checked_sub(FIRST_INDEX)? as usize));
+        prompt.extend(value.map(String::as_str));
+    }
+    prompt
+}
+
+/// Renders `PS2` like `PS1`, before a command is read: the readers of its continuation lines
+/// have no state at hand to expand it with.
+pub fn render_continuation<R, E>(inter: &Interpreter<R, E>, state: &mut ShellState)
+where
+    R: Fn() -> Option<String>,
+    E: Fn(&mut ShellState, Vec<Command>) -> i32,
+{
+    let prompt = render(inter, state, "PS2", "> ");
+    CONTINUATION.with_borrow_mut(|continuation| *continuation = Some(prompt));
+}
+
+/// the prompt of continuation lines, `PS2` as rendered last, or only decoded when it never was
+pub fn continuation() -> String {
+    CONTINUATION
+        .with_borrow(Clone::clone)
+        .unwrap_or_else(|| decode(&env::var("PS2").unwrap_or_else(|_| "> ".into()), None))
+}
+
+/// Decodes the bash escapes of a prompt, those about the state of the shell are left out without it.
+pub fn decode(prompt: &str, state: Option<&ShellState>) -> String {
+    let mut decoded = String::new();
+    decode_with(prompt, state, |text, _| decoded.push_str(text));
+    decoded
+}
+
+/// Decodes the bash escapes of a prompt, `push` is given each piece of it in turn,
+/// with whether it's the value of an escape.
+fn decode_with(prompt: &str, state: Option<&ShellState>, mut push: impl FnMut(&str, bool)) {
+    let mut chars = prompt.chars();
+
+    while let Some(ch) = chars.next() {
+        if ch != '\\' {
+            push(ch.encode_utf8(&mut [0; 4]), false);
+            continue;
+        }
+
+        let Some(escape) = chars.next() else {
+            push("\\", false);
+            break;
+        };
+        let value = match escape {
+            'u' => username(),
+            'h' => hostname().split('.').next().unwrap_or_default().to_string(),
+            'H' => hostname(),
+            'w' => working_dir(),
+            'W' => {
+                let dir = working_dir();
+                match dir.as_str() {
+                    "/" | "~" => dir,
+                    _ => dir.rsplit('/').next().unwrap_or_default().to_string(),
+                }
+            }
+            '$' => if unsafe { geteuid() } == 0 { "#" } else { "$" }.to_string(),
+            't' => Local::now().format("%H:%M:%S").to_string(),
+            'T' => Local::now().format("%I:%M:%S").to_string(),
+            '@' => Local::now().format("%I:%M %p").to_string(),
+            'A' => Local::now().format("%H:%M").to_string(),
+            'd' => Local::now().format("%a %b %d").to_string(),
+            's' => "0-shell".to_string(),
+            'v' => env!("CARGO_PKG_VERSION").to_string(),
+            '!' => with_history(|history| history.first_number() + history.entries().len()).to_string(),
+            '?' => state.map(|state| state.last_status.to_string()).unwrap_or_default(),
+            'g' => git_prompt::segment(git_prompt::DEFAULT_FORMAT).unwrap_or_default(),
+            'j' => state.map(|state| state.jobs.iter().count().to_string()).unwrap_or_default(),
+            'n' => "\n".to_string(),
+            'r' => "\r".to_string(),
+            'a' => "\x07".to_string(),
+            'e' => "\x1b".to_string(),
+            '[' => START_IGNORE.to_string(),
+            ']' => END_IGNORE.to_string(),
+            '\\' => "\\".to_string(),
+            _ => {
+                push("\\", false);
+                push(escape.encode_utf8(&mut [0; 4]), false);
+                continue;
+            }
+        };
+        push(&value, true);
+    }
+}
+
+fn username() -> String {
+    users::get_current_username()
+        .map(|name| name.to_string_lossy().into_owned())
+        .or_else(|| env::var("USER").ok())
+        .unwrap_or_default()
+}
+
+fn hostname() -> String {
+    let mut buf = [0 as c_char; 256];
+    if unsafe { gethostname(buf.as_mut_ptr(), buf.len()) } == -1 {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(buf.as_ptr()) }.to_string_lossy().into_owned()
+}
+
+/// the working directory as tracked by `cd`, with the home directory shown as `~`
+fn working_dir() -> String {
+    let pwd = env::var("PWD")
+        .ok()
+        .or_else(|| env::current_dir().ok().map(|dir| dir.to_string_lossy().into_owned()))
+        .unwrap_or_default();
+
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() && home != "/" && (pwd == home || pwd.starts_with(&format!("{home}/"))) => {
+            format!("~{}", &pwd[home.len()..])
+        }
+        _ => pwd,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_are_taken_as_they_are() {
+        let inter = Interpreter::new(|| None, |_: &mut ShellState, _: Vec<Command>| 0);
+        let mut state = ShellState {
+            last_status: 3,
+            ..Default::default()
+        };
+        let prompt = render(&inter, &mut state, "0SHELL_TEST_UNSET", "\\s: $? \\? '\\\\' \\$ foo");
+        let sign = if unsafe { geteuid() } == 0 { '#' } else { '$' };
+        assert_eq!(prompt, format!("0-shell: 3 3 \\ {sign} foo"));
+        assert_eq!(state.last_status, 3);
+    }
+}
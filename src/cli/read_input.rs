@@ -1,47 +1,185 @@
 use crate::{
-    cli::{self, run_command},
+    cli::{
+        self,
+        completer::ShellCompleter,
+        editor::{self, Completer},
+        exit_shell, prompt, run_command, startup,
+    },
+    cmd::exit::warn_stopped_jobs,
     interpreter::interpreter::Interpreter,
+    shell::{ShellState, history::with_history, options, signals},
 };
-use core::error;
+use libc::{SIGINT, STDIN_FILENO, STDOUT_FILENO, c_void, isatty};
 use std::{
-    io::{Write, stderr, stdin},
-    process::exit,
+    env,
+    io::{self, ErrorKind, Write, stderr},
 };
 
-pub fn read_line() -> String {
-    let mut input = String::new();
-    if let Err(e) = stdin().read_line(&mut input) {
-        let _ = writeln!(stderr(), "{e}");
-        exit(0)
+/// Reads a line from standard input one byte at a time, so nothing past the line is consumed
+/// and commands sharing the input still get the rest of it.
+///
+/// Returns `None` at the end of input, a signal interrupting the read is reported as an error.
+pub fn read_raw_line() -> io::Result<Option<String>> {
+    let mut line = Vec::new();
+
+    loop {
+        let mut byte = 0u8;
+        let n = unsafe { libc::read(STDIN_FILENO, &mut byte as *mut u8 as *mut c_void, 1) };
+        match n {
+            -1 => return Err(io::Error::last_os_error()),
+            0 if line.is_empty() => return Ok(None),
+            0 => break,
+            _ => {
+                line.push(byte);
+                if byte == b'\n' {
+                    break;
+                }
+            }
+        }
     }
-    if input.is_empty() {
-        exit(0)
+
+    Ok(Some(String::from_utf8_lossy(&line).into_owned()))
+}
+
+/// Prints `prompt` and reads a line, with the line editor when both ends are a terminal.
+pub fn prompt_line(prompt: &str, completer: &mut dyn Completer) -> io::Result<Option<String>> {
+    if unsafe { isatty(STDIN_FILENO) == 1 && isatty(STDOUT_FILENO) == 1 } {
+        return editor::read_line(prompt, completer);
     }
 
-    return input;
+    let _ = cli::print(&prompt.replace([prompt::START_IGNORE, prompt::END_IGNORE], ""));
+    read_raw_line()
+}
+
+/// Reads a continuation line of a command, `None` at the end of input.
+pub fn read_line() -> Option<String> {
+    loop {
+        match prompt_line(&prompt::continuation(), &mut ()) {
+            Ok(Some(input)) => {
+                with_history(|history| history.extend(&input));
+                options::echo_verbose(&input);
+                return Some(input);
+            }
+            Ok(None) => return None,
+            // an interrupted continuation line is read again
+            Err(e) if e.kind() == ErrorKind::Interrupted => {
+                let _ = cli::print("\n");
+                continue;
+            }
+            Err(e) => {
+                let _ = writeln!(stderr(), "{e}");
+                return None;
+            }
+        }
+    }
 }
 
 pub fn read_input() {
     let inter = Interpreter::new(read_line, run_command);
+    let options = startup::Options::parse();
+    let mut state = ShellState {
+        interactive: options.script.is_none() && unsafe { isatty(STDIN_FILENO) } == 1,
+        login: options.login,
+        name: options.script.clone().or_else(|| env::args().next()).unwrap_or_default(),
+        positional: options.args.clone(),
+        ..Default::default()
+    };
+    if state.interactive {
+        signals::init_interactive();
+        state.jobs.enable_job_control(state.interactive);
+    }
+
+    // the startup files may set `HISTFILE` and `HISTSIZE`
+    startup::run_startup_files(&inter, &mut state, &options);
+    if let Some(script) = &options.script {
+        let status = startup::run_script(&inter, &mut state, script);
+        exit_shell(&mut state, status)
+    }
+    if state.interactive {
+        with_history(|history| history.load());
+    }
 
+    // consecutive ends of input ignored with `ignoreeof`
+    let mut ignored_eofs = 0;
     loop {
-        let input = read_line();
+        inter.run_traps(&mut state);
+        state.jobs.notify();
+        // the warning about stopped jobs only holds for the command right after it
+        let warned = state.exit_warned;
+        let prompt = prompt::render(&inter, &mut state, "PS1", "$ ");
+        prompt::render_continuation(&inter, &mut state);
+        let input = match prompt_line(&prompt, &mut ShellCompleter { state: &mut state }) {
+            Ok(Some(input)) => {
+                ignored_eofs = 0;
+                input
+            }
+            Ok(None) => {
+                if state.interactive && ignored_eofs < eofs_ignored(&state) {
+                    ignored_eofs += 1;
+                    eprintln!("Use \"exit\" to leave the shell.");
+                    continue;
+                }
+                if state.interactive {
+                    eprintln!("exit");
+                }
+                if warn_stopped_jobs(&mut state) {
+                    continue;
+                }
+                let last_status = state.last_status;
+                exit_shell(&mut state, last_status)
+            }
+            // Ctrl-C discards the line being typed, and runs the trap on `SIGINT` before the next prompt
+            Err(e) if e.kind() == ErrorKind::Interrupted => {
+                let _ = cli::print("\n");
+                state.last_status = 130;
+                signals::set_pending(SIGINT);
+                continue;
+            }
+            Err(e) => {
+                let _ = writeln!(stderr(), "{e}");
+                exit_shell(&mut state, 1)
+            }
+        };
+
+        options::echo_verbose(&input);
         if input.trim().is_empty() {
-            let _ = cli::print("$ ");
             continue;
         }
 
-        let commands = inter.parse_line(&input);
-        for command in commands {
-            if let Some(error) = command.error {
-                eprint!("{error}");
-                continue;
+        // `!` references are only expanded in interactive shells
+        let expansion = match state.interactive {
+            true => with_history(|history| history.expand(&input)),
+            false => Ok(None),
+        };
+        let input = match expansion {
+            // the expanded line is shown before being run
+            Ok(Some(expanded)) => {
+                let _ = cli::print(&expanded);
+                expanded
             }
-            if command.name.trim().is_empty() {
+            Ok(None) => input,
+            Err(e) => {
+                eprintln!("0-shell: {e}");
+                state.last_status = 1;
                 continue;
             }
-            let exit_status = run_command(command);
+        };
+
+        with_history(|history| history.start(&input));
+        inter.parse_line(&mut state, &input);
+        with_history(|history| history.finish());
+        if warned {
+            state.exit_warned = false;
         }
-        let _ = cli::print("$ ");
+    }
+}
+
+/// How many ends of input in a row an interactive shell ignores: none unless `ignoreeof` is set
+/// or `IGNOREEOF` is, whose value is the count (10 when it isn't a number).
+fn eofs_ignored(state: &ShellState) -> u32 {
+    match env::var("IGNOREEOF") {
+        Ok(count) => count.trim().parse().unwrap_or(10),
+        Err(_) if state.options.ignoreeof => 10,
+        Err(_) => 0,
     }
 }
@@ -1,70 +1,214 @@
-use std::process::exit;
+use std::io::{ErrorKind, PipeReader, pipe};
+use std::mem;
+use std::os::fd::AsRawFd;
+use std::path::PathBuf;
+use std::os::unix::process::{CommandExt, ExitStatusExt};
+use std::process::{self, exit};
 
-use libc::{STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, c_int, close, fork, waitpid};
+use libc::{STDIN_FILENO, STDOUT_FILENO, dup2, fork};
 
-use crate::cmd::{clear::clear, *};
-use crate::interpreter::command::Command;
+use crate::cmd::{
+    builtin::{self, Io},
+    command,
+};
+use crate::cli::{read_line, startup};
+use crate::interpreter::command::{Command, IoStreams, finish_copies};
+use crate::interpreter::interpreter::Interpreter;
+use crate::interpreter::tokens::Operator;
+use crate::shell::{ShellState, history::with_history};
+use crate::utils::error::StrError;
 
-// forks a command and returns exit status
-pub fn run_command(cmd: Command) -> i32 {
+/// Forks a process for each command of the pipeline, all in the same process group,
+/// and returns the exit status of the last one.
+///
+/// A pipeline ending with `&` is registered as a background job and not waited for.
+pub fn run_command(state: &mut ShellState, mut pipeline: Vec<Command>) -> i32 {
+    let background = pipeline.last().is_some_and(|cmd| cmd.terminator == Some(Operator::And));
+    for cmd in &mut pipeline {
+        command::bypass(cmd);
+        // programs are looked up before forking so the shell remembers where they are
+        let function = !cmd.skip_functions && state.functions.contains_key(&cmd.name);
+        if !function && builtin::get(&cmd.name).is_none() {
+            cmd.program = state.hashed.find(&cmd.name, true);
+        }
+    }
+
+    // functions and builtins that affect the shell itself, with their redirections in place
+    // while they run, a background one runs in a subshell instead
+    if let [cmd] = pipeline.as_mut_slice()
+        && !background
+    {
+        let function = !cmd.skip_functions && state.functions.contains_key(&cmd.name);
+        let builtin = builtin::get(&cmd.name).filter(|builtin| !function && builtin.parent());
+        if function || builtin.is_some() {
+            let streams = match builtin.is_some_and(|builtin| builtin.redirects()) {
+                true => IoStreams::default(),
+                false => mem::take(&mut cmd.io_streams),
+            };
+            let redirected = match streams.apply() {
+                Ok(redirected) => redirected,
+                Err(e) => {
+                    eprintln!("0-shell: {}", e.str());
+                    return 1;
+                }
+            };
+            let status = match builtin {
+                Some(builtin) => {
+                    let mut io = Io {
+                        streams: &mut cmd.io_streams,
+                        child: false,
+                        redirected: false,
+                    };
+                    builtin.run(state, &cmd.args, &mut io)
+                }
+                None => Interpreter::new(read_line, run_command).run_function(state, &cmd.name, &cmd.args),
+            };
+            redirected.restore();
+            return status;
+        }
+    }
+
+    let text = pipeline.iter().map(Command::text).collect::<Vec<_>>().join(" | ");
+    let count = pipeline.len();
+    let mut pids = Vec::new();
+    let mut pgid = 0;
+    let mut input: Option<PipeReader> = None;
+
+    for (i, cmd) in pipeline.into_iter().enumerate() {
+        let output = if i + 1 < count {
+            match pipe() {
+                Ok(p) => Some(p),
+                Err(e) => {
+                    eprintln!("0-shell: pipe failed: {e}");
+                    break;
+                }
+            }
+        } else {
+            None
+        };
+
+        let pid = unsafe { fork() };
+        if pid == -1 {
+            eprintln!("0-shell: fork failed: {}", std::io::Error::last_os_error());
+            break;
+        }
+
+        // child
+        if pid == 0 {
+            state.jobs.enter_process_group(pgid, !background);
+            state.subshell();
+
+            if let Some(reader) = input {
+                unsafe { dup2(reader.as_raw_fd(), STDIN_FILENO) };
+            }
+            if let Some((reader, writer)) = output {
+                drop(reader);
+                unsafe { dup2(writer.as_raw_fd(), STDOUT_FILENO) };
+            }
+
+            exit(run_child(state, cmd));
+        }
+
+        state.jobs.set_process_group(pid, pgid);
+        if pgid == 0 {
+            pgid = pid;
+        }
+        pids.push(pid);
+
+        // the read end is kept for the next command, the write end belongs to the child only
+        input = output.map(|(reader, _)| reader);
+    }
+
+    if pids.is_empty() {
+        return 1;
+    }
+
+    let id = state.jobs.add(pgid, pids, text);
+    if background {
+        let pid = state.jobs.get(id).map_or(pgid, |job| job.pid());
+        state.last_background = Some(pid);
+        eprintln!("[{id}] {pid}");
+        return 0;
+    }
+
+    state.jobs.foreground(id, false)
+}
+
+/// runs the logout file and the `EXIT` trap, then terminates the shell
+pub fn exit_shell(state: &mut ShellState, status: i32) -> ! {
+    with_history(|history| history.finish());
+    // `$?` in the `EXIT` trap is the status the shell exits with
+    state.last_status = status;
+    let inter = Interpreter::new(read_line, run_command);
+    startup::run_logout_file(&inter, state);
+    inter.run_exit_trap(state);
+    state.finish_redirections();
+    exit(status)
+}
+
+/// Runs a single command inside a forked child and returns its exit status.
+fn run_child(state: &mut ShellState, cmd: Command) -> i32 {
     let Command {
         name,
         ref args,
         io_streams,
+        skip_functions,
+        program,
         ..
     } = cmd;
-    
-    // let mut exit_status =
-    match name.as_str() {
-        "cd" => return cd::cd(args),
-        "clear" => return clear(),
-        "exit" => exit(0),
-        _ => {}
-    }
-
-    // if exit_status != 0 {
-    //     exit(exit_status);
-    // }
 
-    let pid = unsafe { fork() };
+    let copiers = match io_streams.apply() {
+        Ok(redirected) => redirected.keep(),
+        Err(e) => {
+            eprintln!("0-shell: {}", e.str());
+            return 1;
+        }
+    };
+    let exit_status = if !skip_functions && state.functions.contains_key(&name) {
+        Interpreter::new(read_line, run_command).run_function(state, &name, args)
+    } else if let Some(builtin) = builtin::get(&name) {
+        let mut io = Io {
+            streams: &mut IoStreams::default(),
+            child: true,
+            redirected: !copiers.is_empty(),
+        };
+        builtin.run(state, args, &mut io)
+    } else {
+        run_external(&name, program, args, copiers.is_empty())
+    };
 
-    if pid == -1 {
-        return 1;
-    }
+    finish_copies(&copiers);
+    exit_status
+}
 
-    // child
-    if pid == 0 {
-        let handlers = io_streams.redirect();
-        let exit_status = match name.as_str() {
-            "echo" => echo(args),
-            "cp" => cp::cp(args),
-            "pwd" => return pwd::pwd(args),
-            "mv" => mv::mv(args),
-            "ls"=> ls::run_ls(args),
-            "mkdir" => mkdir::mkdir(args),
-            "cat" => cat::cat(args),
-            "rm" => rm::rm(args),
-            _ => {
-                eprintln!("Command '{}' not found", name);
-                127
-            }
-        };
+/// Runs the program `name`, found at `path` in `PATH` when it's known.
+///
+/// When `replace` is set the current process is replaced by the program, otherwise it runs
+/// as a child so the processes copying its output can be waited for once it's done.
+pub fn run_external(name: &str, path: Option<PathBuf>, args: &[String], replace: bool) -> i32 {
+    let mut program = match path {
+        Some(path) => process::Command::new(path),
+        None => process::Command::new(name),
+    };
+    program.arg0(name).args(args);
 
-        unsafe {
-            close(STDIN_FILENO);
-            close(STDOUT_FILENO);
-            close(STDERR_FILENO);
-        }
-        for h in handlers {
-            let _ = h.join();
+    let err = if replace {
+        program.exec()
+    } else {
+        match program.status() {
+            Ok(status) => return status.code().unwrap_or_else(|| 128 + status.signal().unwrap_or(0)),
+            Err(e) => e,
         }
-        exit(exit_status);
     };
 
-    let status = 0 as *mut c_int;
-    let wid = unsafe { waitpid(pid, status, 0) };
-    if wid != pid {
-        return 1;
+    match err.kind() {
+        ErrorKind::NotFound => {
+            eprintln!("Command '{}' not found", name);
+            127
+        }
+        _ => {
+            eprintln!("0-shell: {name}: {}", err.str());
+            126
+        }
     }
-    return status as i32;
 }
@@ -0,0 +1,143 @@
+use std::{
+    env, fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process::exit,
+};
+
+use crate::{
+    interpreter::{command::Command, interpreter::Interpreter},
+    shell::ShellState,
+    utils::error::clear_error,
+};
+
+const USAGE: &str = "usage: 0-shell [-l | --login] [--norc] [--noprofile] [file [arg ...]]";
+
+/// The options the shell is started with.
+#[derive(Default)]
+pub struct Options {
+    /// `-l`, `--login` or a name starting with `-`: the profiles are read
+    pub login: bool,
+    /// `--norc`: an interactive shell doesn't read `~/.0shellrc` nor `$ENV`
+    pub norc: bool,
+    /// `--noprofile`: a login shell doesn't read the profiles
+    pub noprofile: bool,
+    /// the script run instead of reading commands from standard input
+    pub script: Option<String>,
+    /// the arguments following the script, its positional parameters
+    pub args: Vec<String>,
+}
+
+impl Options {
+    /// Parses the arguments of the shell, exits with a usage message on an invalid one.
+    pub fn parse() -> Self {
+        let mut args = env::args();
+        let mut options = Self {
+            login: args.next().is_some_and(|name| name.starts_with('-')),
+            ..Default::default()
+        };
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "-l" | "--login" => options.login = true,
+                "--norc" => options.norc = true,
+                "--noprofile" => options.noprofile = true,
+                // the first operand is the script, the following ones are its arguments
+                "--" => {
+                    options.script = args.next();
+                    options.args = args.collect();
+                    break;
+                }
+                _ if !arg.starts_with('-') => {
+                    options.script = Some(arg);
+                    options.args = args.collect();
+                    break;
+                }
+                _ => {
+                    eprintln!("0-shell: {arg}: invalid option");
+                    eprintln!("{USAGE}");
+                    exit(2);
+                }
+            }
+        }
+
+        options
+    }
+}
+
+/// Sources the startup files: `/etc/0shell_profile` and `~/.0shell_profile` for a login shell,
+/// then `$ENV`, or `~/.0shellrc` when it's unset, for an interactive shell.
+///
+/// `/etc/profile` and `~/.profile` are left out: they are written for shells with `if`, `for`
+/// and `case`, which this one can't parse.
+pub fn run_startup_files<R, E>(inter: &Interpreter<R, E>, state: &mut ShellState, options: &Options)
+where
+    R: Fn() -> Option<String>,
+    E: Fn(&mut ShellState, Vec<Command>) -> i32,
+{
+    if options.login && !options.noprofile {
+        source_file(inter, state, Path::new("/etc/0shell_profile"));
+        if let Some(profile) = home_file(".0shell_profile") {
+            source_file(inter, state, &profile);
+        }
+    }
+
+    if state.interactive && !options.norc {
+        let rc = match env::var("ENV") {
+            // parameters in `ENV` are expanded
+            Ok(file) if !file.is_empty() => Some(PathBuf::from(inter.expand(state, &file))),
+            _ => home_file(".0shellrc"),
+        };
+        if let Some(rc) = rc {
+            source_file(inter, state, &rc);
+        }
+    }
+}
+
+/// Runs the commands of the script `path`, returns the status of the last one,
+/// or 127 when it can't be read.
+pub fn run_script<R, E>(inter: &Interpreter<R, E>, state: &mut ShellState, path: &str) -> i32
+where
+    R: Fn() -> Option<String>,
+    E: Fn(&mut ShellState, Vec<Command>) -> i32,
+{
+    match fs::read_to_string(path) {
+        Ok(content) => inter.run_source(state, &content),
+        Err(e) => {
+            eprintln!("0-shell: {path}: {}", clear_error(e));
+            127
+        }
+    }
+}
+
+/// sources `~/.0shell_logout` when a login shell exits
+pub fn run_logout_file<R, E>(inter: &Interpreter<R, E>, state: &mut ShellState)
+where
+    R: Fn() -> Option<String>,
+    E: Fn(&mut ShellState, Vec<Command>) -> i32,
+{
+    if state.login
+        && let Some(logout) = home_file(".0shell_logout")
+    {
+        source_file(inter, state, &logout);
+    }
+}
+
+/// Runs the commands of `path`, a missing file is skipped silently.
+fn source_file<R, E>(inter: &Interpreter<R, E>, state: &mut ShellState, path: &Path)
+where
+    R: Fn() -> Option<String>,
+    E: Fn(&mut ShellState, Vec<Command>) -> i32,
+{
+    match fs::read_to_string(path) {
+        Ok(content) => {
+            inter.run_source(state, &content);
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => eprintln!("0-shell: {}: {}", path.display(), clear_error(e)),
+    }
+}
+
+fn home_file(name: &str) -> Option<PathBuf> {
+    env::var("HOME").ok().filter(|home| !home.is_empty()).map(|home| Path::new(&home).join(name))
+}
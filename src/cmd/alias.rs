@@ -0,0 +1,75 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{
+        ShellState,
+        aliases::{self, with_aliases},
+    },
+    utils::quote::quote,
+};
+
+/// `alias [-p] [name[=value] ...]` defines aliases, or prints the ones named,
+/// all of them without operands.
+pub fn alias(args: &[String]) -> i32 {
+    let args: Vec<&String> = args.iter().skip_while(|arg| *arg == "-p").collect();
+    if let Some(arg) = args.first().filter(|arg| arg.starts_with('-') && **arg != "--") {
+        eprintln!("0-shell: alias: {arg}: invalid option");
+        eprintln!("alias: usage: alias [-p] [name[=value] ... ]");
+        return 2;
+    }
+    let args = args.into_iter().skip_while(|arg| *arg == "--");
+
+    let mut status = 0;
+    let mut listed = false;
+    for arg in args {
+        listed = true;
+        match arg.split_once('=') {
+            Some((name, _)) if !aliases::is_valid_name(name) => {
+                eprintln!("0-shell: alias: `{name}': invalid alias name");
+                status = 1;
+            }
+            Some((name, value)) => {
+                with_aliases(|aliases| aliases.insert(name.to_string(), value.to_string()));
+            }
+            None => match aliases::get(arg) {
+                Some(value) => println!("alias {arg}={}", quote(&value)),
+                None => {
+                    eprintln!("0-shell: alias: {arg}: not found");
+                    status = 1;
+                }
+            },
+        }
+    }
+
+    if !listed {
+        with_aliases(|aliases| {
+            for (name, value) in aliases.iter() {
+                println!("alias {name}={}", quote(value));
+            }
+        });
+    }
+    status
+}
+
+pub struct Alias;
+
+impl Builtin for Alias {
+    fn name(&self) -> &'static str {
+        "alias"
+    }
+
+    fn usage(&self) -> &'static str {
+        "alias [-p] [name[=value] ... ]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-p"]
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        alias(args)
+    }
+}
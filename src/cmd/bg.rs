@@ -0,0 +1,64 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{ShellState, jobs::JobStatus},
+};
+
+/// `bg [%job]...` resumes stopped jobs in the background, the current job by default.
+pub fn bg(state: &mut ShellState, args: &[String]) -> i32 {
+    if !state.jobs.job_control() {
+        eprintln!("0-shell: bg: no job control");
+        return 1;
+    }
+
+    let specs: Vec<Option<&String>> = if args.is_empty() {
+        vec![None]
+    } else {
+        args.iter().map(Some).collect()
+    };
+
+    let mut exit_status = 0;
+    for spec in specs {
+        let job = match state.jobs.find_or_current(spec) {
+            Ok(job) => job,
+            Err(e) => {
+                eprintln!("0-shell: bg: {e}");
+                exit_status = 1;
+                continue;
+            }
+        };
+
+        if job.status() == JobStatus::Running {
+            eprintln!("0-shell: bg: job {} already in background", job.id);
+            continue;
+        }
+
+        let id = job.id;
+        println!("[{id}]{} {} &", state.jobs.marker(id), job.command);
+        state.jobs.resume_background(id);
+        if let Some(job) = state.jobs.get(id) {
+            state.last_background = Some(job.pid());
+        }
+    }
+
+    exit_status
+}
+
+pub struct Bg;
+
+impl Builtin for Bg {
+    fn name(&self) -> &'static str {
+        "bg"
+    }
+
+    fn usage(&self) -> &'static str {
+        "bg [job_spec ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        bg(state, args)
+    }
+}
@@ -0,0 +1,108 @@
+use crate::{
+    cmd::*,
+    interpreter::command::IoStreams,
+    shell::ShellState,
+};
+
+/// What a builtin is given besides its arguments.
+pub struct Io<'a> {
+    /// the redirections of the command, left to a builtin making them itself,
+    /// already in place for the others
+    pub streams: &'a mut IoStreams,
+    /// set when the builtin runs in a forked child
+    pub child: bool,
+    /// set in a child whose descriptors are copied to several files by other processes,
+    /// which it waits for before exiting
+    pub redirected: bool,
+}
+
+/// A command run by the shell itself.
+pub trait Builtin: Sync {
+    fn name(&self) -> &'static str;
+
+    /// the synopsis printed by `help`
+    fn usage(&self) -> &'static str;
+
+    /// Whether it changes the shell, which must then run it itself rather than in a child.
+    /// In a pipeline or in the background it runs in a child all the same.
+    fn parent(&self) -> bool {
+        false
+    }
+
+    /// Whether it makes the redirections of its command itself, for them to outlast it,
+    /// rather than have them undone once it's done.
+    fn redirects(&self) -> bool {
+        false
+    }
+
+    /// the options completed after its name
+    fn options(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], io: &mut Io) -> i32;
+}
+
+/// the builtins, sorted by name
+pub static REGISTRY: &[&dyn Builtin] = &[
+    &test::Bracket,
+    &alias::Alias,
+    &bg::Bg,
+    &cat::Cat,
+    &cd::Cd,
+    &clear::Clear,
+    &command::Command,
+    &compgen::Compgen,
+    &complete::Complete,
+    &cp::Cp,
+    &disown::Disown,
+    &echo::Echo,
+    &eval::Eval,
+    &exec::Exec,
+    &exit::Exit,
+    &fg::Fg,
+    &getopts::Getopts,
+    &git_prompt::GitPrompt,
+    &hash::Hash,
+    &help::Help,
+    &history::History,
+    &jobs::Jobs,
+    &kill::Kill,
+    &ls::Ls,
+    &mkdir::Mkdir,
+    &mv::Mv,
+    &printf::Printf,
+    &pwd::Pwd,
+    &read::Read,
+    &rm::Rm,
+    &set::Set,
+    &shift::Shift,
+    &test::Test,
+    &trap::Trap,
+    &r#type::Type,
+    &unalias::Unalias,
+    &wait::Wait,
+    &which::Which,
+];
+
+/// the builtin called `name`
+pub fn get(name: &str) -> Option<&'static dyn Builtin> {
+    REGISTRY.binary_search_by(|builtin| builtin.name().cmp(name)).ok().map(|i| REGISTRY[i])
+}
+
+/// the names of the builtins, sorted
+pub fn names() -> impl Iterator<Item = &'static str> {
+    REGISTRY.iter().map(|builtin| builtin.name())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_sorted() {
+        // `get` searches the registry by dichotomy
+        assert!(REGISTRY.is_sorted_by_key(|builtin| builtin.name()));
+        assert!(REGISTRY.iter().all(|builtin| get(builtin.name()).is_some()));
+    }
+}
@@ -1,6 +1,8 @@
 use crate::utils::error::clear_error;
 use std::fs;
 use std::io::{self, BufRead, Write};
+use crate::cmd::builtin::{Builtin, Io};
+use crate::shell::ShellState;
 
 // Reads from standard input line by line and writes it to standard output
 fn read_input() {
@@ -62,3 +64,19 @@ pub fn cat(args: &[String]) -> i32 {
 
     if all_ok { 0 } else { 1 }
 }
+
+pub struct Cat;
+
+impl Builtin for Cat {
+    fn name(&self) -> &'static str {
+        "cat"
+    }
+
+    fn usage(&self) -> &'static str {
+        "cat [file ...]"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        cat(args)
+    }
+}
@@ -1,5 +1,7 @@
 use crate::utils::error::clear_error;
 use std::{env, path::{Path, PathBuf}};
+use crate::cmd::builtin::{Builtin, Io};
+use crate::shell::ShellState;
 
 pub fn cd(args: &[String]) -> i32 {
     if args.len() > 1 {
@@ -130,4 +132,24 @@ fn simple_path(path: &Path) -> String {
     }
 
     res[start..].to_string()
-}
\ No newline at end of file
+}
+
+pub struct Cd;
+
+impl Builtin for Cd {
+    fn name(&self) -> &'static str {
+        "cd"
+    }
+
+    fn usage(&self) -> &'static str {
+        "cd [dir]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        cd(args)
+    }
+}
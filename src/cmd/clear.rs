@@ -1,7 +1,32 @@
 use std::io::{Write, stdout};
 
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+};
+
 pub fn clear() -> i32 {
     print!("\x1b[H\x1b[2J\x1b[3J");
     stdout().flush().unwrap();
     return 0;
 }
+
+pub struct Clear;
+
+impl Builtin for Clear {
+    fn name(&self) -> &'static str {
+        "clear"
+    }
+
+    fn usage(&self) -> &'static str {
+        "clear"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, _: &mut ShellState, _: &[String], _: &mut Io) -> i32 {
+        clear()
+    }
+}
@@ -0,0 +1,162 @@
+use std::path::PathBuf;
+
+use crate::{
+    cmd::builtin::{self, Builtin, Io},
+    interpreter::{self, parser::KEYWORDS},
+    shell::{ShellState, aliases, path},
+    utils::quote::quote,
+};
+
+const USAGE: &str = "command: usage: command [-vV] name [arg ...]";
+
+/// What a command name stands for.
+pub enum Resolution {
+    Alias(String),
+    Keyword,
+    Function,
+    Builtin,
+    File(PathBuf),
+    /// an executable whose path the shell remembers
+    Hashed(PathBuf),
+}
+
+impl Resolution {
+    /// the word `type -t` prints
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Resolution::Alias(_) => "alias",
+            Resolution::Keyword => "keyword",
+            Resolution::Function => "function",
+            Resolution::Builtin => "builtin",
+            Resolution::File(_) | Resolution::Hashed(_) => "file",
+        }
+    }
+}
+
+/// What `name` stands for, in the order the shell looks it up:
+/// all of them with `all`, otherwise the one it runs only.
+pub fn resolve(state: &ShellState, name: &str, all: bool) -> Vec<Resolution> {
+    let mut found = Vec::new();
+    if let Some(value) = aliases::get(name) {
+        found.push(Resolution::Alias(value));
+    }
+    if KEYWORDS.contains(&name) {
+        found.push(Resolution::Keyword);
+    }
+    if state.functions.contains_key(name) {
+        found.push(Resolution::Function);
+    }
+    if builtin::get(name).is_some() {
+        found.push(Resolution::Builtin);
+    }
+    if !all && !found.is_empty() {
+        found.truncate(1);
+        return found;
+    }
+
+    match state.hashed.get(name) {
+        Some(hashed) if !all => found.push(Resolution::Hashed(hashed.to_path_buf())),
+        _ if all => found.extend(path::search(name).into_iter().map(Resolution::File)),
+        _ => found.extend(path::find(name).map(Resolution::File)),
+    }
+    found
+}
+
+/// Turns `command name [arg ...]` into `name [arg ...]`, which then runs without
+/// being looked up among the functions. `command -v` and `command -V` are left as they are.
+pub fn bypass(cmd: &mut interpreter::command::Command) {
+    while cmd.name == "command" {
+        let rest = match cmd.args.first().map(String::as_str) {
+            Some("--") => &cmd.args[1..],
+            Some(arg) if arg.starts_with('-') && arg != "-" => return,
+            _ => &cmd.args[..],
+        };
+        let Some((name, args)) = rest.split_first() else {
+            return;
+        };
+
+        (cmd.name, cmd.args) = (name.clone(), args.to_vec());
+        cmd.skip_functions = true;
+    }
+}
+
+/// `command -v name...` prints how the shell runs each name: the command defining an alias,
+/// the path of an executable, or the name itself. `command -V name...` describes them.
+///
+/// `command name [arg ...]` is run by [`bypass`], the status is 1 when a name isn't found.
+pub fn command(state: &ShellState, args: &[String]) -> i32 {
+    let mut verbose = None;
+    let mut i = 0;
+    while let Some(arg) = args.get(i).filter(|arg| arg.starts_with('-') && arg.len() > 1) {
+        i += 1;
+        if arg == "--" {
+            break;
+        }
+        for flag in arg.chars().skip(1) {
+            match flag {
+                'v' => verbose = verbose.or(Some(false)),
+                'V' => verbose = Some(true),
+                _ => {
+                    eprintln!("0-shell: command: -{flag}: invalid option");
+                    eprintln!("{USAGE}");
+                    return 2;
+                }
+            }
+        }
+    }
+    let Some(verbose) = verbose else {
+        return 0;
+    };
+
+    let mut status = 0;
+    for name in &args[i..] {
+        let Some(resolution) = resolve(state, name, false).into_iter().next() else {
+            if verbose {
+                eprintln!("0-shell: command: {name}: not found");
+            }
+            status = 1;
+            continue;
+        };
+        match verbose {
+            true => println!("{}", describe(name, &resolution)),
+            false => match resolution {
+                Resolution::Alias(value) => println!("alias {name}={}", quote(&value)),
+                Resolution::File(path) | Resolution::Hashed(path) => println!("{}", path.display()),
+                _ => println!("{name}"),
+            },
+        }
+    }
+    status
+}
+
+/// a sentence telling what `name` stands for
+pub fn describe(name: &str, resolution: &Resolution) -> String {
+    match resolution {
+        Resolution::Alias(value) => format!("{name} is aliased to `{value}'"),
+        Resolution::Keyword => format!("{name} is a shell keyword"),
+        Resolution::Function => format!("{name} is a function"),
+        Resolution::Builtin => format!("{name} is a shell builtin"),
+        Resolution::File(path) => format!("{name} is {}", path.display()),
+        Resolution::Hashed(path) => format!("{name} is hashed ({})", path.display()),
+    }
+}
+
+pub struct Command;
+
+impl Builtin for Command {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
+    fn usage(&self) -> &'static str {
+        "command [-vV] name [arg ...]"
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-V", "-v"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        command(state, args)
+    }
+}
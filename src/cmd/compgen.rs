@@ -0,0 +1,51 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{ShellState, completion::CompSpec},
+};
+
+/// `compgen [-dfc] [-W wordlist] [word]` prints the completions of `word`, one per line.
+pub fn compgen(state: &mut ShellState, args: &[String]) -> i32 {
+    let (spec, _, operands) = match CompSpec::parse("compgen", args, "") {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("0-shell: {e}");
+            eprintln!("compgen: usage: compgen [-dfc] [-W wordlist] [word]");
+            return 2;
+        }
+    };
+
+    if spec.function.is_some() {
+        eprintln!("0-shell: compgen: -F: only supported by complete");
+        return 2;
+    }
+
+    let word = operands.first().map_or("", String::as_str);
+    let functions: Vec<String> = state.functions.keys().cloned().collect();
+    let candidates = spec.generate(word, &functions);
+    for candidate in &candidates {
+        println!("{candidate}");
+    }
+
+    // like bash, nothing to complete is a failure
+    if candidates.is_empty() { 1 } else { 0 }
+}
+
+pub struct Compgen;
+
+impl Builtin for Compgen {
+    fn name(&self) -> &'static str {
+        "compgen"
+    }
+
+    fn usage(&self) -> &'static str {
+        "compgen [-dfc] [-W wordlist] [word]"
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-W", "-c", "-d", "-f"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        compgen(state, args)
+    }
+}
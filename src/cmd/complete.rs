@@ -0,0 +1,86 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{ShellState, completion::CompSpec},
+};
+
+const USAGE: &str = "complete: usage: complete [-pr] [-dfc] [-W wordlist] [-F function] [name ...]";
+
+/// `complete [-dfc] [-W wordlist] [-F function] name...` sets how the arguments of the commands `name` are
+/// completed, `-p` prints the specifications and `-r` removes them.
+///
+/// A function given with `-F` finds the words of the line in the array `COMP_WORDS`, and sets
+/// `COMPREPLY` to the candidates, one per line, or to an array of them.
+pub fn complete(state: &mut ShellState, args: &[String]) -> i32 {
+    let (spec, flags, names) = match CompSpec::parse("complete", args, "pr") {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("0-shell: {e}");
+            eprintln!("{USAGE}");
+            return 2;
+        }
+    };
+
+    if flags.contains('r') {
+        if names.is_empty() {
+            state.completions.clear();
+        }
+        for name in &names {
+            state.completions.remove(name);
+        }
+        return 0;
+    }
+
+    if flags.contains('p') || args.is_empty() {
+        if names.is_empty() {
+            for (name, spec) in &state.completions {
+                println!("{}", spec.to_command(name));
+            }
+            return 0;
+        }
+
+        let mut exit_status = 0;
+        for name in &names {
+            match state.completions.get(name) {
+                Some(spec) => println!("{}", spec.to_command(name)),
+                None => {
+                    eprintln!("0-shell: complete: {name}: no completion specification");
+                    exit_status = 1;
+                }
+            }
+        }
+        return exit_status;
+    }
+
+    if names.is_empty() {
+        eprintln!("{USAGE}");
+        return 2;
+    }
+    for name in names {
+        state.completions.insert(name, spec.clone());
+    }
+    0
+}
+
+pub struct Complete;
+
+impl Builtin for Complete {
+    fn name(&self) -> &'static str {
+        "complete"
+    }
+
+    fn usage(&self) -> &'static str {
+        "complete [-pr] [-dfc] [-W wordlist] [-F function] [name ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-F", "-W", "-c", "-d", "-f", "-p", "-r"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        complete(state, args)
+    }
+}
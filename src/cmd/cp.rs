@@ -1,5 +1,7 @@
 use crate::utils::error::StrError;
 use std::fs;
+use crate::cmd::builtin::{Builtin, Io};
+use crate::shell::ShellState;
 
 /// minimal implementation of cp utility according to [POSIX: cp](https://pubs.opengroup.org/onlinepubs/9699919799/utilities/cp.html)
 ///
@@ -93,3 +95,19 @@ fn perform_copy(src: &str, target: &str) -> i32 {
 
     return 0;
 }
+
+pub struct Cp;
+
+impl Builtin for Cp {
+    fn name(&self) -> &'static str {
+        "cp"
+    }
+
+    fn usage(&self) -> &'static str {
+        "cp source ... target"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        cp(args)
+    }
+}
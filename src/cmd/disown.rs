@@ -0,0 +1,86 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{ShellState, jobs::JobStatus},
+};
+
+/// `disown [-a|-r] [%job]...` removes jobs from the job table, the current job by default,
+/// their processes being reaped once they end but no longer reported,
+/// `-a` removes every job and `-r` only the running ones.
+pub fn disown(state: &mut ShellState, args: &[String]) -> i32 {
+    let mut all = false;
+    let mut running = false;
+    let mut specs = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "-a" => all = true,
+            "-r" => running = true,
+            "--" => {}
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                eprintln!("0-shell: disown: {arg}: invalid option");
+                eprintln!("disown: usage: disown [-ar] [jobspec ... | pid ...]");
+                return 2;
+            }
+            _ => specs.push(arg),
+        }
+    }
+
+    if all || running {
+        let ids: Vec<usize> = state
+            .jobs
+            .iter()
+            .filter(|job| !running || job.status() == JobStatus::Running)
+            .map(|job| job.id)
+            .collect();
+        for id in ids {
+            state.jobs.disown(id);
+        }
+        return 0;
+    }
+
+    let specs: Vec<Option<&String>> = if specs.is_empty() {
+        vec![None]
+    } else {
+        specs.into_iter().map(Some).collect()
+    };
+
+    let mut exit_status = 0;
+    for spec in specs {
+        match state.jobs.find_or_current(spec) {
+            Ok(job) => {
+                let id = job.id;
+                state.jobs.disown(id);
+            }
+            Err(e) => {
+                eprintln!("0-shell: disown: {e}");
+                exit_status = 1;
+            }
+        }
+    }
+
+    exit_status
+}
+
+pub struct Disown;
+
+impl Builtin for Disown {
+    fn name(&self) -> &'static str {
+        "disown"
+    }
+
+    fn usage(&self) -> &'static str {
+        "disown [-ar] [jobspec ... | pid ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-a", "-r"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        disown(state, args)
+    }
+}
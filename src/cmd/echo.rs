@@ -1,4 +1,77 @@
-pub fn echo(args: &[String]) -> i32 {
-    println!("{}", args.join(" "));
-    return 0;
+use std::io::{Write, stdout};
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+    utils::escape::{self, Escapes},
+};
+
+/// `echo [-neE] [arg ...]` writes its arguments separated by spaces and followed by a newline.
+///
+/// `-n` leaves out the newline, `-e` expands backslash escapes and `-E` doesn't,
+/// which is the default unless the `xpg_echo` option is set. Only leading arguments made
+/// of these letters alone are options, `\c` in an expanded argument ends the output.
+pub fn echo(state: &ShellState, args: &[String]) -> i32 {
+    let mut newline = true;
+    let mut escapes = state.options.xpg_echo;
+
+    let options = args
+        .iter()
+        .take_while(|arg| arg.len() > 1 && arg.starts_with('-') && arg[1..].chars().all(|ch| "neE".contains(ch)))
+        .count();
+    for flag in args[..options].iter().flat_map(|arg| arg[1..].chars()) {
+        match flag {
+            'n' => newline = false,
+            'e' => escapes = true,
+            _ => escapes = false,
+        }
+    }
+
+    let mut output = Vec::new();
+    for (i, arg) in args[options..].iter().enumerate() {
+        if i > 0 {
+            output.push(b' ');
+        }
+        if !escapes {
+            output.extend(arg.as_bytes());
+            continue;
+        }
+
+        let (bytes, stop) = escape::expand(arg, Escapes::Echo);
+        output.extend(bytes);
+        if stop {
+            newline = false;
+            break;
+        }
+    }
+    if newline {
+        output.push(b'\n');
+    }
+
+    let mut out = stdout().lock();
+    if let Err(e) = out.write_all(&output).and_then(|_| out.flush()) {
+        eprintln!("0-shell: echo: write error: {e}");
+        return 1;
+    }
+    0
+}
+
+pub struct Echo;
+
+impl Builtin for Echo {
+    fn name(&self) -> &'static str {
+        "echo"
+    }
+
+    fn usage(&self) -> &'static str {
+        "echo [-neE] [arg ...]"
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-E", "-e", "-n"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        echo(state, args)
+    }
 }
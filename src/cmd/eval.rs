@@ -0,0 +1,36 @@
+use crate::{
+    cli::{read_line, run_command},
+    cmd::builtin::{Builtin, Io},
+    interpreter::interpreter::Interpreter,
+    shell::ShellState,
+};
+
+/// `eval [arg ...]` joins its arguments with spaces and runs the result as a command line,
+/// the status is the one of the last command run, 0 when there is none.
+pub fn eval(state: &mut ShellState, args: &[String]) -> i32 {
+    let line = args.join(" ");
+    if line.trim().is_empty() {
+        return 0;
+    }
+    Interpreter::new(read_line, run_command).run_source(state, &line)
+}
+
+pub struct Eval;
+
+impl Builtin for Eval {
+    fn name(&self) -> &'static str {
+        "eval"
+    }
+
+    fn usage(&self) -> &'static str {
+        "eval [arg ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        eval(state, args)
+    }
+}
@@ -0,0 +1,112 @@
+use libc::c_int;
+use std::{
+    io::{Write, stdout},
+    mem,
+    os::unix::process::CommandExt,
+    process,
+};
+
+use crate::{
+    cli::{exit_shell, run_external},
+    cmd::builtin::{Builtin, Io},
+    interpreter::command::IoStreams,
+    shell::{ShellState, signals},
+    utils::error::StrError,
+};
+
+/// `exec [name [arg ...]]` replaces the shell with the program `name`, its redirections in place.
+///
+/// Without a name, the redirections of `exec` stay in effect for the shell and the commands
+/// it runs afterwards.
+///
+/// Returns `Err` with the status of `exec` when the shell keeps running, and `Ok` with the status
+/// it exits with when `name` can't be run by a non-interactive shell.
+pub fn exec(state: &mut ShellState, args: &[String], io_streams: &mut IoStreams) -> Result<i32, i32> {
+    let args = match args.first() {
+        Some(first) if first == "--" => &args[1..],
+        _ => args,
+    };
+    if let Some(option) = args.first().filter(|arg| arg.starts_with('-') && arg.len() > 1) {
+        eprintln!("0-shell: exec: {option}: invalid option");
+        eprintln!("exec: usage: exec [command [argument ...]]");
+        return Err(2);
+    }
+
+    let program = match args.first() {
+        Some(name) => match state.hashed.find(name, true) {
+            Some(program) => Some(program),
+            None => {
+                eprintln!("0-shell: exec: {name}: not found");
+                return failed(state, 127);
+            }
+        },
+        None => None,
+    };
+
+    let redirected = match mem::take(io_streams).apply() {
+        Ok(redirected) => redirected,
+        Err(e) => {
+            eprintln!("0-shell: exec: {}", e.str());
+            return Err(1);
+        }
+    };
+    let (Some(program), Some((name, args))) = (program, args.split_first()) else {
+        state.redirections.extend(redirected.keep());
+        return Err(0);
+    };
+
+    let _ = stdout().flush();
+    let ignored: Vec<c_int> = state.traps.iter().filter(|(_, action)| action.is_empty()).map(|(sig, _)| *sig).collect();
+    signals::reset_signals(&ignored);
+    let err = process::Command::new(&program).arg0(name).args(args).exec();
+
+    // the shell keeps running as it was, its signal dispositions are set back
+    redirected.restore();
+    if state.interactive {
+        signals::init_interactive();
+    }
+    for (sig, action) in state.traps.clone() {
+        state.set_trap(sig, Some(action));
+    }
+    eprintln!("0-shell: exec: {name}: {}", err.str());
+    failed(state, 126)
+}
+
+/// the result of `exec` failing to run a program: an interactive shell keeps running
+fn failed(state: &ShellState, status: i32) -> Result<i32, i32> {
+    if state.interactive { Err(status) } else { Ok(status) }
+}
+
+pub struct Exec;
+
+impl Builtin for Exec {
+    fn name(&self) -> &'static str {
+        "exec"
+    }
+
+    fn usage(&self) -> &'static str {
+        "exec [command [argument ...]]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn redirects(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], io: &mut Io) -> i32 {
+        // in a child, the program replaces it
+        if io.child {
+            return match args.split_first() {
+                Some((name, args)) => run_external(name, state.hashed.find(name, true), args, !io.redirected),
+                None => 0,
+            };
+        }
+        match exec(state, args, io.streams) {
+            Ok(status) => exit_shell(state, status),
+            Err(status) => status,
+        }
+    }
+}
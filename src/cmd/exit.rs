@@ -0,0 +1,73 @@
+use crate::{
+    cli::exit_shell,
+    cmd::builtin::{Builtin, Io},
+    shell::{ShellState, jobs::JobStatus},
+};
+
+/// `exit [n]` checks the status the shell terminates with: `n` modulo 256,
+/// or the status of the last command when omitted.
+///
+/// Returns `Err` with the status of the command when the shell must keep running.
+pub fn exit(state: &mut ShellState, args: &[String]) -> Result<i32, i32> {
+    if state.interactive {
+        eprintln!("exit");
+    }
+
+    let status = match args {
+        [] => state.last_status,
+        [n] => match n.trim().parse::<i64>() {
+            Ok(n) => (n & 0xff) as i32,
+            Err(_) => {
+                eprintln!("0-shell: exit: {n}: numeric argument required");
+                return Ok(2);
+            }
+        },
+        _ => {
+            eprintln!("0-shell: exit: too many arguments");
+            return Err(1);
+        }
+    };
+
+    if warn_stopped_jobs(state) {
+        return Err(1);
+    }
+    Ok(status)
+}
+
+/// Warns once about stopped jobs before an interactive shell exits, returns whether it did.
+pub fn warn_stopped_jobs(state: &mut ShellState) -> bool {
+    if !state.interactive || state.exit_warned {
+        return false;
+    }
+
+    state.jobs.reap();
+    let stopped = state.jobs.iter().any(|job| matches!(job.status(), JobStatus::Stopped(_)));
+    if stopped {
+        eprintln!("There are stopped jobs.");
+        state.exit_warned = true;
+    }
+    stopped
+}
+
+pub struct Exit;
+
+impl Builtin for Exit {
+    fn name(&self) -> &'static str {
+        "exit"
+    }
+
+    fn usage(&self) -> &'static str {
+        "exit [n]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], io: &mut Io) -> i32 {
+        match exit(state, args) {
+            Ok(status) if !io.child => exit_shell(state, status),
+            Ok(status) | Err(status) => status,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+};
+
+/// `fg [%job]` resumes a job in the foreground, the current job by default.
+pub fn fg(state: &mut ShellState, args: &[String]) -> i32 {
+    if !state.jobs.job_control() {
+        eprintln!("0-shell: fg: no job control");
+        return 1;
+    }
+
+    let id = match state.jobs.find_or_current(args.first()) {
+        Ok(job) => {
+            println!("{}", job.command);
+            job.id
+        }
+        Err(e) => {
+            eprintln!("0-shell: fg: {e}");
+            return 1;
+        }
+    };
+
+    state.jobs.foreground(id, true)
+}
+
+pub struct Fg;
+
+impl Builtin for Fg {
+    fn name(&self) -> &'static str {
+        "fg"
+    }
+
+    fn usage(&self) -> &'static str {
+        "fg [job_spec]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        fg(state, args)
+    }
+}
@@ -0,0 +1,122 @@
+use std::env;
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    interpreter::interpreter::is_name,
+    shell::ShellState,
+};
+
+const USAGE: &str = "getopts: usage: getopts optstring name [arg ...]";
+
+/// `getopts optstring name [arg ...]` sets `name` to the next option of the arguments,
+/// the positional parameters when none are given, and `OPTIND` to the index of the next
+/// argument to look at. An option followed by `:` in `optstring` takes an argument, set to `OPTARG`.
+///
+/// An unknown option or a missing argument sets `name` to `?` and is reported, unless
+/// `optstring` starts with `:`: `OPTARG` is then set to the option and `name` to `:` for a missing
+/// argument. The status is 1 once the options are over, at the first operand or after `--`.
+pub fn getopts(state: &mut ShellState, args: &[String]) -> i32 {
+    let [optstring, name, rest @ ..] = args else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+    if !is_name(name) {
+        eprintln!("0-shell: getopts: `{name}': not a valid identifier");
+        return 1;
+    }
+    let params = match rest.is_empty() {
+        true => state.positional.clone(),
+        false => rest.to_vec(),
+    };
+    let (silent, optstring) = match optstring.strip_prefix(':') {
+        Some(optstring) => (true, optstring),
+        None => (false, optstring.as_str()),
+    };
+    let report = !silent && env::var("OPTERR").map_or(true, |opterr| opterr.trim() != "0");
+
+    let mut optind = env::var("OPTIND").ok().and_then(|optind| optind.trim().parse().ok()).unwrap_or(1).max(1);
+    // an `OPTIND` other than the one set last means the arguments are looked at anew
+    let mut pos = match state.getopts {
+        (last, pos) if last == optind => pos,
+        _ => 1,
+    };
+
+    let arg: Vec<char> = params.get(optind - 1).map(|arg| arg.chars().collect()).unwrap_or_default();
+    if pos == 1 || pos >= arg.len() {
+        pos = 1;
+        if arg.len() < 2 || arg[0] != '-' || arg == ['-', '-'] {
+            if arg == ['-', '-'] {
+                optind += 1;
+            }
+            state.set_var(name, Some("?"));
+            state.set_var("OPTARG", None);
+            finish(state, optind, 1);
+            return 1;
+        }
+    }
+
+    let option = arg[pos];
+    pos += 1;
+    let last = pos >= arg.len();
+    let known = option != ':' && optstring.contains(option);
+    let takes_argument = optstring.chars().skip_while(|&ch| ch != option).nth(1) == Some(':');
+
+    let value = if !known {
+        if report {
+            eprintln!("0-shell: illegal option -- {option}");
+        }
+        state.set_var("OPTARG", silent.then(|| option.to_string()).as_deref());
+        '?'
+    } else if !takes_argument {
+        state.set_var("OPTARG", None);
+        option
+    } else if !last {
+        state.set_var("OPTARG", Some(&arg[pos..].iter().collect::<String>()));
+        pos = arg.len();
+        option
+    } else if let Some(optarg) = params.get(optind) {
+        state.set_var("OPTARG", Some(optarg));
+        optind += 1;
+        option
+    } else {
+        if report {
+            eprintln!("0-shell: option requires an argument -- {option}");
+        }
+        state.set_var("OPTARG", silent.then(|| option.to_string()).as_deref());
+        if silent { ':' } else { '?' }
+    };
+
+    if pos >= arg.len() {
+        optind += 1;
+        pos = 1;
+    }
+    state.set_var(name, Some(&value.to_string()));
+    finish(state, optind, pos);
+    0
+}
+
+/// sets `OPTIND` and remembers where the next option is
+fn finish(state: &mut ShellState, optind: usize, pos: usize) {
+    state.set_var("OPTIND", Some(&optind.to_string()));
+    state.getopts = (optind, pos);
+}
+
+pub struct Getopts;
+
+impl Builtin for Getopts {
+    fn name(&self) -> &'static str {
+        "getopts"
+    }
+
+    fn usage(&self) -> &'static str {
+        "getopts optstring name [arg ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        getopts(state, args)
+    }
+}
@@ -0,0 +1,56 @@
+use std::{
+    env,
+    io::{Write, stdout},
+    path::PathBuf,
+};
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+    utils::git,
+};
+
+/// default format of the segment, as `__git_ps1` has
+pub const DEFAULT_FORMAT: &str = " (%s)";
+
+/// `git_prompt [format]` prints the state of the git repository of the working directory
+/// through `format`, `%s` standing for the state. Outside of a repository nothing is printed.
+pub fn git_prompt(args: &[String]) -> i32 {
+    if args.len() > 1 {
+        eprintln!("0-shell: git_prompt: too many arguments");
+        return 2;
+    }
+
+    let format = args.first().map_or(DEFAULT_FORMAT, String::as_str);
+    match segment(format) {
+        Some(segment) => {
+            print!("{segment}");
+            let _ = stdout().flush();
+            0
+        }
+        None => 1,
+    }
+}
+
+/// the state of the repository of the working directory through `format`, `None` outside of a repository
+pub fn segment(format: &str) -> Option<String> {
+    let dir = env::var("PWD").map(PathBuf::from).or_else(|_| env::current_dir()).ok()?;
+    let status = git::status(&dir)?;
+    Some(format.replace("%s", &status.to_string()))
+}
+
+pub struct GitPrompt;
+
+impl Builtin for GitPrompt {
+    fn name(&self) -> &'static str {
+        "git_prompt"
+    }
+
+    fn usage(&self) -> &'static str {
+        "git_prompt [format]"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        git_prompt(args)
+    }
+}
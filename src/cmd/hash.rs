@@ -0,0 +1,114 @@
+use std::path::PathBuf;
+
+use crate::{
+    cmd::builtin::{self, Builtin, Io},
+    shell::ShellState,
+};
+
+const USAGE: &str = "hash: usage: hash [-r] [-p pathname] [name ...]";
+
+/// `hash [-r] [-p pathname] [name ...]` remembers where the executables named are in `PATH`,
+/// without names it lists the ones remembered and how many times they were run.
+///
+/// `-r` forgets them all and `-p pathname` remembers `pathname` for the name following it.
+/// The status is 1 when a name isn't found.
+pub fn hash(state: &mut ShellState, args: &[String]) -> i32 {
+    let mut pathname = None;
+    let mut reset = false;
+    let mut i = 0;
+    while let Some(arg) = args.get(i).filter(|arg| arg.starts_with('-') && arg.len() > 1) {
+        i += 1;
+        if arg == "--" {
+            break;
+        }
+        for (pos, flag) in arg.char_indices().skip(1) {
+            match flag {
+                'r' => reset = true,
+                'p' => {
+                    // the path is the rest of the argument, or the next one
+                    let rest = &arg[pos + 1..];
+                    pathname = match rest.is_empty() {
+                        true => {
+                            i += 1;
+                            args.get(i - 1).cloned()
+                        }
+                        false => Some(rest.to_string()),
+                    };
+                    if pathname.is_none() {
+                        eprintln!("0-shell: hash: -p: option requires an argument");
+                        eprintln!("{USAGE}");
+                        return 2;
+                    }
+                    break;
+                }
+                _ => {
+                    eprintln!("0-shell: hash: -{flag}: invalid option");
+                    eprintln!("{USAGE}");
+                    return 2;
+                }
+            }
+        }
+    }
+    let names = &args[i..];
+    if reset {
+        state.hashed.clear();
+    }
+
+    if let Some(pathname) = pathname {
+        if let Some(name) = names.first() {
+            state.hashed.insert(name, PathBuf::from(pathname));
+        }
+        return 0;
+    }
+    if names.is_empty() && !reset {
+        return list(state);
+    }
+
+    let mut status = 0;
+    // builtins and paths aren't looked up
+    for name in names.iter().filter(|name| !name.contains('/') && builtin::get(name).is_none()) {
+        if state.hashed.find(name, false).is_none() {
+            eprintln!("0-shell: hash: {name}: not found");
+            status = 1;
+        }
+    }
+    status
+}
+
+fn list(state: &ShellState) -> i32 {
+    let mut entries = state.hashed.iter().peekable();
+    if entries.peek().is_none() {
+        println!("hash: hash table empty");
+        return 0;
+    }
+
+    println!("hits\tcommand");
+    for (_, entry) in entries {
+        println!("{:4}\t{}", entry.hits, entry.path.display());
+    }
+    0
+}
+
+pub struct Hash;
+
+impl Builtin for Hash {
+    fn name(&self) -> &'static str {
+        "hash"
+    }
+
+    fn usage(&self) -> &'static str {
+        "hash [-r] [-p pathname] [name ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-p", "-r"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        hash(state, args)
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{
+    cmd::builtin::{Builtin, Io, REGISTRY},
+    shell::ShellState,
+    utils::pattern,
+};
+
+/// `help [pattern ...]` prints the synopsis of the builtins whose name matches a pattern,
+/// or of all of them. The status is 1 when a pattern matches none.
+pub fn help(args: &[String]) -> i32 {
+    if args.is_empty() {
+        for builtin in REGISTRY {
+            println!("{}", builtin.usage());
+        }
+        return 0;
+    }
+
+    let mut status = 0;
+    for pattern in args {
+        let mut matching = REGISTRY.iter().filter(|builtin| pattern::matches(pattern, builtin.name())).peekable();
+        if matching.peek().is_none() {
+            eprintln!("0-shell: help: no help topics match `{pattern}'.");
+            status = 1;
+        }
+        for builtin in matching {
+            println!("{}: {}", builtin.name(), builtin.usage());
+        }
+    }
+    status
+}
+
+pub struct Help;
+
+impl Builtin for Help {
+    fn name(&self) -> &'static str {
+        "help"
+    }
+
+    fn usage(&self) -> &'static str {
+        "help [pattern ...]"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        help(args)
+    }
+}
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{
+        ShellState,
+        history::{self, with_history},
+    },
+};
+
+const USAGE: &str = "history: usage: history [-c] [-d offset] [n] or history -w [filename]";
+
+/// `history [n]` lists the last `n` commands, `-c` clears the history,
+/// `-d offset` deletes an entry and `-w [file]` writes the history to the history file or `file`.
+pub fn history(args: &[String]) -> i32 {
+    with_history(|history| run(history, args))
+}
+
+fn run(history: &mut history::History, args: &[String]) -> i32 {
+    let mut args = args.iter().peekable();
+    let mut count = None;
+    let mut list = true;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--" => {}
+            "-c" => {
+                history.clear();
+                list = false;
+            }
+            "-d" => {
+                let Some(offset) = args.next() else {
+                    eprintln!("0-shell: history: -d: option requires an argument");
+                    eprintln!("{USAGE}");
+                    return 2;
+                };
+                let Ok(number) = offset.parse::<isize>() else {
+                    eprintln!("0-shell: history: {offset}: history position out of range");
+                    return 1;
+                };
+                if let Err(e) = history.delete(number) {
+                    eprintln!("0-shell: history: {e}");
+                    return 1;
+                }
+                list = false;
+            }
+            "-w" => {
+                let path = args.next().map(PathBuf::from);
+                if let Err(e) = history.write(path) {
+                    eprintln!("0-shell: history: {e}");
+                    return 1;
+                }
+                return 0;
+            }
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                eprintln!("0-shell: history: {arg}: invalid option");
+                eprintln!("{USAGE}");
+                return 2;
+            }
+            _ => match arg.parse::<usize>() {
+                Ok(n) if args.peek().is_none() => count = Some(n),
+                Ok(_) => {
+                    eprintln!("0-shell: history: too many arguments");
+                    return 1;
+                }
+                Err(_) => {
+                    eprintln!("0-shell: history: {arg}: numeric argument required");
+                    return 1;
+                }
+            },
+        }
+    }
+
+    if !list {
+        return 0;
+    }
+
+    let entries = history.entries();
+    let skip = entries.len().saturating_sub(count.unwrap_or(entries.len()));
+    for (i, entry) in entries.iter().enumerate().skip(skip) {
+        println!("{:>5}  {entry}", history.first_number() + i);
+    }
+    0
+}
+
+pub struct History;
+
+impl Builtin for History {
+    fn name(&self) -> &'static str {
+        "history"
+    }
+
+    fn usage(&self) -> &'static str {
+        "history [-c] [-d offset] [n] or history -w [filename]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-c", "-d", "-w"]
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        history(args)
+    }
+}
@@ -0,0 +1,89 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+};
+
+/// `jobs [-l|-p] [%job]...` lists the jobs of the shell,
+/// `-l` adds the process ids and `-p` prints only the process group ids.
+pub fn jobs(state: &mut ShellState, args: &[String]) -> i32 {
+    let mut long = false;
+    let mut pids_only = false;
+    let mut specs = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "--" => continue,
+            _ if arg.starts_with('-') && arg.len() > 1 => {
+                for ch in arg[1..].chars() {
+                    match ch {
+                        'l' => long = true,
+                        'p' => pids_only = true,
+                        _ => {
+                            eprintln!("0-shell: jobs: -{ch}: invalid option");
+                            eprintln!("jobs: usage: jobs [-lp] [jobspec ...]");
+                            return 2;
+                        }
+                    }
+                }
+            }
+            _ => specs.push(arg),
+        }
+    }
+
+    state.jobs.reap();
+
+    let mut ids = Vec::new();
+    let mut exit_status = 0;
+    if specs.is_empty() {
+        ids.extend(state.jobs.iter().map(|job| job.id));
+    }
+    for spec in specs {
+        match state.jobs.find(spec) {
+            Ok(job) => ids.push(job.id),
+            Err(e) => {
+                eprintln!("0-shell: jobs: {e}");
+                exit_status = 1;
+            }
+        }
+    }
+
+    for id in ids {
+        let Some(job) = state.jobs.get(id) else {
+            continue;
+        };
+
+        if pids_only {
+            println!("{}", job.pgid);
+        } else {
+            println!("{}", state.jobs.format(job, long));
+        }
+    }
+
+    // listed jobs don't need a notification anymore
+    state.jobs.forget_reported();
+    exit_status
+}
+
+pub struct Jobs;
+
+impl Builtin for Jobs {
+    fn name(&self) -> &'static str {
+        "jobs"
+    }
+
+    fn usage(&self) -> &'static str {
+        "jobs [-lp] [jobspec ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-l", "-p"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        jobs(state, args)
+    }
+}
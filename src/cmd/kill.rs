@@ -0,0 +1,144 @@
+use libc::{SIGCONT, SIGHUP, SIGTERM, c_int, pid_t};
+
+use crate::shell::{
+    ShellState,
+    jobs::JobStatus,
+    signals::{SIGNALS, signal_number, signal_to_name},
+};
+use crate::utils::error::StrError;
+use crate::cmd::builtin::{Builtin, Io};
+
+/// `kill [-s sig | -n num | -sig] pid|%job...` sends a signal (`TERM` by default) to processes or jobs,
+/// `kill -l [status]` lists the signal names.
+pub fn kill(state: &mut ShellState, args: &[String]) -> i32 {
+    let mut signal = SIGTERM;
+    let mut operands = args;
+
+    match args.first().map(String::as_str) {
+        None => {
+            eprintln!("kill: usage: kill [-s sigspec | -n signum | -sigspec] pid | jobspec ...");
+            return 2;
+        }
+        Some("-l" | "-L") => return list_signals(&args[1..]),
+        Some("-s" | "-n") => {
+            let Some(spec) = args.get(1) else {
+                eprintln!("0-shell: kill: {}: option requires an argument", args[0]);
+                return 2;
+            };
+            let Some(sig) = signal_number(spec) else {
+                eprintln!("0-shell: kill: {spec}: invalid signal specification");
+                return 1;
+            };
+            signal = sig;
+            operands = &args[2..];
+        }
+        Some("--") => operands = &args[1..],
+        Some(arg) if arg.starts_with('-') && arg.len() > 1 => {
+            let Some(sig) = signal_number(&arg[1..]) else {
+                eprintln!("0-shell: kill: {}: invalid signal specification", &arg[1..]);
+                return 1;
+            };
+            signal = sig;
+            operands = &args[1..];
+        }
+        Some(_) => {}
+    }
+
+    if operands.is_empty() {
+        eprintln!("kill: usage: kill [-s sigspec | -n signum | -sigspec] pid | jobspec ...");
+        return 2;
+    }
+
+    let mut exit_status = 0;
+    for operand in operands {
+        let result = if operand.starts_with('%') {
+            match state.jobs.find(operand) {
+                Ok(job) => {
+                    let result = state.jobs.signal(job, signal).map_err(|e| format!("({}) - {}", job.pgid, e.str()));
+                    // a stopped job has to be continued to act upon the signal
+                    if matches!(job.status(), JobStatus::Stopped(_)) && matches!(signal, SIGTERM | SIGHUP) {
+                        let _ = state.jobs.signal(job, SIGCONT);
+                    }
+                    result
+                }
+                Err(e) => Err(e),
+            }
+        } else {
+            match operand.parse::<pid_t>() {
+                Ok(pid) => send(pid, signal),
+                Err(_) => Err(format!("{operand}: arguments must be process or job IDs")),
+            }
+        };
+
+        if let Err(e) = result {
+            eprintln!("0-shell: kill: {e}");
+            exit_status = 1;
+        }
+    }
+
+    exit_status
+}
+
+fn send(pid: pid_t, signal: c_int) -> Result<(), String> {
+    if unsafe { libc::kill(pid, signal) } == -1 {
+        return Err(format!("({}) - {}", pid.abs(), std::io::Error::last_os_error().str()));
+    }
+    Ok(())
+}
+
+/// without operands every signal is listed, otherwise each signal number or exit status is translated
+fn list_signals(operands: &[String]) -> i32 {
+    if operands.is_empty() {
+        for (sig, name) in SIGNALS {
+            println!("{sig:>2}) SIG{name}");
+        }
+        return 0;
+    }
+
+    let mut exit_status = 0;
+    for operand in operands {
+        match operand.parse::<c_int>() {
+            // exit statuses of processes killed by a signal are accepted too
+            Ok(number) => match signal_to_name(if number > 128 { number - 128 } else { number }) {
+                Some(name) => println!("{name}"),
+                None => {
+                    eprintln!("0-shell: kill: {operand}: invalid signal specification");
+                    exit_status = 1;
+                }
+            },
+            Err(_) => match signal_number(operand) {
+                Some(sig) => println!("{sig}"),
+                None => {
+                    eprintln!("0-shell: kill: {operand}: invalid signal specification");
+                    exit_status = 1;
+                }
+            },
+        }
+    }
+
+    exit_status
+}
+
+pub struct Kill;
+
+impl Builtin for Kill {
+    fn name(&self) -> &'static str {
+        "kill"
+    }
+
+    fn usage(&self) -> &'static str {
+        "kill [-s sigspec | -n signum | -sigspec] pid | jobspec ... or kill -l [sigspec]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-l", "-n", "-s"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        kill(state, args)
+    }
+}
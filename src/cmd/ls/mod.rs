@@ -4,4 +4,29 @@ pub mod entries;
 pub mod entry; 
 pub mod utils; 
 
-pub use ls::run_ls;
\ No newline at end of file
+pub use ls::run_ls;
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+};
+
+pub struct Ls;
+
+impl Builtin for Ls {
+    fn name(&self) -> &'static str {
+        "ls"
+    }
+
+    fn usage(&self) -> &'static str {
+        "ls [-Fal] [file ...]"
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-F", "-a", "-l"]
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        run_ls(&args.to_vec())
+    }
+}
@@ -1,5 +1,7 @@
 use std::fs;
 use crate::utils::error::{clear_error};
+use crate::cmd::builtin::{Builtin, Io};
+use crate::shell::ShellState;
 
 
 pub fn mkdir(args: &[String]) -> i32 {
@@ -22,3 +24,19 @@ pub fn mkdir(args: &[String]) -> i32 {
 
     if counter > 0 { 1 } else { 0 }
 }
+
+pub struct Mkdir;
+
+impl Builtin for Mkdir {
+    fn name(&self) -> &'static str {
+        "mkdir"
+    }
+
+    fn usage(&self) -> &'static str {
+        "mkdir directory ..."
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        mkdir(args)
+    }
+}
@@ -1,12 +1,38 @@
+pub mod alias;
+pub mod bg;
+pub mod builtin;
 pub mod cat;
 pub mod cd;
 pub mod clear;
+pub mod command;
+pub mod compgen;
+pub mod complete;
 pub mod cp;
+pub mod disown;
 pub mod echo;
+pub mod eval;
+pub mod exec;
+pub mod exit;
+pub mod fg;
+pub mod getopts;
+pub mod git_prompt;
+pub mod hash;
+pub mod help;
+pub mod history;
+pub mod jobs;
+pub mod kill;
 pub mod ls;
 pub mod mkdir;
 pub mod mv;
+pub mod printf;
 pub mod pwd;
+pub mod read;
 pub mod rm;
-
-pub use echo::echo;
+pub mod set;
+pub mod shift;
+pub mod test;
+pub mod trap;
+pub mod r#type;
+pub mod unalias;
+pub mod wait;
+pub mod which;
@@ -1,6 +1,8 @@
 use std::fs;
 use std::path::Path;
 use crate::utils::error::{clear_error};
+use crate::cmd::builtin::{Builtin, Io};
+use crate::shell::ShellState;
 
 
 // we handle just one by one here , one source to the destination
@@ -92,3 +94,19 @@ pub fn mv(args: &[String]) -> i32 {
 
     if all_ok { 0 } else { 1 }
 }
+
+pub struct Mv;
+
+impl Builtin for Mv {
+    fn name(&self) -> &'static str {
+        "mv"
+    }
+
+    fn usage(&self) -> &'static str {
+        "mv source ... target"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        mv(args)
+    }
+}
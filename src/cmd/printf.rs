@@ -0,0 +1,459 @@
+use std::io::{self, Write, stdout};
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+    utils::escape::{self, Escape, Escapes},
+};
+
+const USAGE: &str = "printf: usage: printf format [arguments]";
+
+/// `printf format [arguments]` writes the arguments as described by `format`.
+///
+/// The format is reused while arguments remain, missing ones are empty strings or zeros.
+/// An argument that isn't a number is reported and taken as far as it is one, the status being 1.
+/// Each part is written to `out` as soon as it's converted, before an error stops the output.
+pub fn printf(args: &[String], out: &mut impl Write) -> i32 {
+    let args = match args.first() {
+        Some(first) if first == "--" => &args[1..],
+        _ => args,
+    };
+    let Some((format, args)) = args.split_first() else {
+        eprintln!("{USAGE}");
+        return 2;
+    };
+
+    let mut printer = Printer {
+        args,
+        next: 0,
+        out,
+        error: None,
+        status: 0,
+    };
+    let format: Vec<char> = format.chars().collect();
+    loop {
+        let start = printer.next;
+        if !printer.print(&format) {
+            break;
+        }
+        // the format is reused when it took arguments and some remain
+        if printer.next == start || printer.next >= args.len() {
+            break;
+        }
+    }
+
+    let flushed = match printer.error.take() {
+        Some(e) => Err(e),
+        None => printer.out.flush(),
+    };
+    if let Err(e) = flushed {
+        eprintln!("0-shell: printf: write error: {e}");
+        return 1;
+    }
+    printer.status
+}
+
+struct Printer<'a, W: Write> {
+    args: &'a [String],
+    /// index of the next argument to convert
+    next: usize,
+    out: &'a mut W,
+    /// the first write that failed, nothing more is written after it
+    error: Option<io::Error>,
+    status: i32,
+}
+
+/// a conversion specification: `%[flags][width][.precision]conversion`
+#[derive(Default)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    alternate: bool,
+    zero: bool,
+    width: usize,
+    precision: Option<usize>,
+}
+
+impl<W: Write> Printer<'_, W> {
+    fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_none()
+            && let Err(e) = self.out.write_all(bytes)
+        {
+            self.error = Some(e);
+        }
+    }
+
+    fn arg(&mut self) -> Option<&str> {
+        let arg = self.args.get(self.next)?;
+        self.next += 1;
+        Some(arg)
+    }
+
+    /// Writes `format` once, returns `false` when nothing more must be written.
+    fn print(&mut self, format: &[char]) -> bool {
+        let mut i = 0;
+        while i < format.len() && self.error.is_none() {
+            match format[i] {
+                '\\' => {
+                    let (escape, len) = escape::escape(&format[i + 1..], Escapes::Format);
+                    if let Escape::Bytes(bytes) = escape {
+                        self.write(&bytes);
+                    }
+                    i += len + 1;
+                }
+                '%' => match self.conversion(&format[i + 1..]) {
+                    Some(len) => i += len + 1,
+                    None => return false,
+                },
+                ch => {
+                    let mut buf = [0; 4];
+                    self.write(ch.encode_utf8(&mut buf).as_bytes());
+                    i += 1;
+                }
+            }
+        }
+        true
+    }
+
+    /// Writes the conversion `spec` starts, returns its length or `None` when the output must stop.
+    fn conversion(&mut self, spec: &[char]) -> Option<usize> {
+        let mut s = Spec::default();
+        let mut i = 0;
+
+        while let Some(&flag) = spec.get(i) {
+            match flag {
+                '-' => s.left = true,
+                '+' => s.plus = true,
+                ' ' => s.space = true,
+                '#' => s.alternate = true,
+                '0' => s.zero = true,
+                _ => break,
+            }
+            i += 1;
+        }
+
+        if spec.get(i) == Some(&'*') {
+            let width = self.arg().map(str::to_string);
+            let width = width.map_or(0, |arg| self.integer(&arg));
+            s.left |= width < 0;
+            s.width = width.unsigned_abs() as usize;
+            i += 1;
+        } else {
+            let digits: String = spec[i..].iter().take_while(|ch| ch.is_ascii_digit()).collect();
+            s.width = digits.parse().unwrap_or(0);
+            i += digits.len();
+        }
+
+        if spec.get(i) == Some(&'.') {
+            i += 1;
+            if spec.get(i) == Some(&'*') {
+                let precision = self.arg().map(str::to_string);
+                let precision = precision.map_or(0, |arg| self.integer(&arg));
+                // a negative precision is taken as omitted
+                s.precision = usize::try_from(precision).ok();
+                i += 1;
+            } else {
+                let digits: String = spec[i..].iter().take_while(|ch| ch.is_ascii_digit()).collect();
+                s.precision = Some(digits.parse().unwrap_or(0));
+                i += digits.len();
+            }
+        }
+
+        // length modifiers change nothing
+        while spec.get(i).is_some_and(|ch| "hlLjzt".contains(*ch)) {
+            i += 1;
+        }
+
+        let Some(&conversion) = spec.get(i) else {
+            eprintln!("0-shell: printf: `%': missing format character");
+            self.status = 1;
+            return None;
+        };
+        // `%%` takes no argument
+        if conversion == '%' {
+            self.write(b"%");
+            return Some(i + 1);
+        }
+        let arg = self.arg().unwrap_or_default().to_string();
+
+        let text = match conversion {
+            's' => {
+                let text: String = match s.precision {
+                    Some(precision) => arg.chars().take(precision).collect(),
+                    None => arg,
+                };
+                pad(&s, "", &text, false)
+            }
+            'b' => {
+                // the bytes are written as they are, should they not be UTF-8
+                let (mut bytes, stop) = escape::expand(&arg, Escapes::Argument);
+                if let Some(precision) = s.precision {
+                    bytes = String::from_utf8_lossy(&bytes).chars().take(precision).collect::<String>().into_bytes();
+                }
+                let len = String::from_utf8_lossy(&bytes).chars().count();
+                let spaces = vec![b' '; s.width.saturating_sub(len)];
+                let bytes = match s.left {
+                    true => [bytes, spaces].concat(),
+                    false => [spaces, bytes].concat(),
+                };
+                self.write(&bytes);
+                return (!stop).then_some(i + 1);
+            }
+            'c' => pad(&s, "", &arg.chars().take(1).collect::<String>(), false),
+            'd' | 'i' => {
+                let value = self.integer(&arg);
+                let sign = sign(&s, value < 0);
+                // the `0` flag is ignored when a precision is given
+                pad(&s, sign, &digits(&s, value.unsigned_abs().to_string()), s.precision.is_none())
+            }
+            'u' | 'o' | 'x' | 'X' => {
+                // negative values wrap around
+                let value = self.integer(&arg) as u64;
+                let text = match conversion {
+                    'u' => value.to_string(),
+                    'o' => format!("{value:o}"),
+                    'x' => format!("{value:x}"),
+                    _ => format!("{value:X}"),
+                };
+                let text = digits(&s, text);
+                let prefix = match conversion {
+                    'o' if s.alternate && !text.starts_with('0') => "0",
+                    'x' if s.alternate && value != 0 => "0x",
+                    'X' if s.alternate && value != 0 => "0X",
+                    _ => "",
+                };
+                pad(&s, prefix, &text, s.precision.is_none())
+            }
+            'f' | 'F' | 'e' | 'E' | 'g' | 'G' => {
+                let value = self.float(&arg);
+                let sign = sign(&s, value.is_sign_negative() && !value.is_nan());
+                let text = float(&s, value.abs(), conversion);
+                pad(&s, sign, &text, value.is_finite())
+            }
+            _ => {
+                eprintln!("0-shell: printf: `{conversion}': invalid format character");
+                self.status = 1;
+                return None;
+            }
+        };
+
+        self.write(text.as_bytes());
+        Some(i + 1)
+    }
+
+    /// The value of an integer argument, decimal, octal with a leading `0`, hexadecimal with `0x`,
+    /// or the code of the character following a quote.
+    fn integer(&mut self, arg: &str) -> i64 {
+        let text = arg.trim_start();
+        if let Some(rest) = text.strip_prefix(['\'', '"']) {
+            return rest.chars().next().map_or(0, |ch| ch as i64);
+        }
+
+        let (negative, unsigned) = match text.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, text.strip_prefix('+').unwrap_or(text)),
+        };
+        let (radix, digits) = match unsigned.strip_prefix("0x").or_else(|| unsigned.strip_prefix("0X")) {
+            Some(hex) => (16, hex),
+            None if unsigned.starts_with('0') => (8, unsigned),
+            None => (10, unsigned),
+        };
+        let len = digits.find(|ch: char| !ch.is_digit(radix)).unwrap_or(digits.len());
+
+        let value = match i64::from_str_radix(&digits[..len], radix) {
+            Ok(value) => value,
+            Err(_) if len == 0 => 0,
+            Err(_) => {
+                eprintln!("0-shell: printf: warning: {arg}: Numerical result out of range");
+                self.status = 1;
+                i64::MAX
+            }
+        };
+        if (len == 0 && !text.is_empty()) || len < digits.len() {
+            eprintln!("0-shell: printf: {arg}: invalid number");
+            self.status = 1;
+        }
+
+        match negative {
+            true => value.wrapping_neg(),
+            false => value,
+        }
+    }
+
+    fn float(&mut self, arg: &str) -> f64 {
+        let text = arg.trim();
+        if let Some(rest) = text.strip_prefix(['\'', '"']) {
+            return rest.chars().next().map_or(0.0, |ch| ch as u32 as f64);
+        }
+        if text.is_empty() {
+            return 0.0;
+        }
+
+        match text.parse() {
+            Ok(value) => value,
+            Err(_) => {
+                eprintln!("0-shell: printf: {arg}: invalid number");
+                self.status = 1;
+                // the longest prefix that is a number
+                (1..text.len())
+                    .rev()
+                    .filter(|&len| text.is_char_boundary(len))
+                    .find_map(|len| text[..len].parse().ok())
+                    .unwrap_or(0.0)
+            }
+        }
+    }
+}
+
+fn sign(s: &Spec, negative: bool) -> &'static str {
+    match negative {
+        true => "-",
+        false if s.plus => "+",
+        false if s.space => " ",
+        false => "",
+    }
+}
+
+/// applies the precision of an integer conversion: its minimum number of digits
+fn digits(s: &Spec, text: String) -> String {
+    match s.precision {
+        Some(0) if text == "0" => String::new(),
+        Some(precision) if text.len() < precision => format!("{}{text}", "0".repeat(precision - text.len())),
+        _ => text,
+    }
+}
+
+/// Fills `text` up to the width with spaces, or with zeros after `prefix` for a number with the `0` flag.
+fn pad(s: &Spec, prefix: &str, text: &str, numeric: bool) -> String {
+    let len = prefix.chars().count() + text.chars().count();
+    let padding = s.width.saturating_sub(len);
+
+    if s.left {
+        format!("{prefix}{text}{}", " ".repeat(padding))
+    } else if s.zero && numeric {
+        format!("{prefix}{}{text}", "0".repeat(padding))
+    } else {
+        format!("{}{prefix}{text}", " ".repeat(padding))
+    }
+}
+
+/// formats a floating point number without its sign, as the C `printf` does
+fn float(s: &Spec, value: f64, conversion: char) -> String {
+    let upper = conversion.is_ascii_uppercase();
+    if !value.is_finite() {
+        let text = if value.is_nan() { "nan" } else { "inf" };
+        return if upper { text.to_uppercase() } else { text.into() };
+    }
+
+    let precision = s.precision.unwrap_or(6);
+    match conversion.to_ascii_lowercase() {
+        'f' => format!("{value:.precision$}"),
+        'e' => exponent(value, precision, upper),
+        _ => {
+            // the style of `%e` is used for exponents below -4 or from the precision on
+            let precision = precision.max(1);
+            let exp = exponent(value, precision - 1, false);
+            let exp: i64 = exp.rsplit_once('e').and_then(|(_, exp)| exp.parse().ok()).unwrap_or(0);
+
+            let text = match exp < -4 || exp >= precision as i64 {
+                true => exponent(value, precision - 1, upper),
+                false => format!("{value:.*}", (precision as i64 - 1 - exp) as usize),
+            };
+            match s.alternate {
+                true => text,
+                false => trim_zeros(&text),
+            }
+        }
+    }
+}
+
+/// formats `value` as `d.ddde+dd`
+fn exponent(value: f64, precision: usize, upper: bool) -> String {
+    let text = format!("{value:.precision$e}");
+    let (mantissa, exp) = text.split_once('e').unwrap_or((&text, "0"));
+    let exp: i32 = exp.parse().unwrap_or(0);
+    let e = if upper { 'E' } else { 'e' };
+    let sign = if exp < 0 { '-' } else { '+' };
+    format!("{mantissa}{e}{sign}{:02}", exp.abs())
+}
+
+/// removes the zeros ending the fraction of a number, and the point when nothing is left after it
+fn trim_zeros(text: &str) -> String {
+    let (number, exp) = match text.find(['e', 'E']) {
+        Some(pos) => text.split_at(pos),
+        None => (text, ""),
+    };
+    let number = match number.contains('.') {
+        true => number.trim_end_matches('0').trim_end_matches('.'),
+        false => number,
+    };
+    format!("{number}{exp}")
+}
+
+pub struct Printf;
+
+impl Builtin for Printf {
+    fn name(&self) -> &'static str {
+        "printf"
+    }
+
+    fn usage(&self) -> &'static str {
+        "printf format [arguments]"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        printf(args, &mut stdout().lock())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the output of `printf` with `args`, and its status
+    fn format(args: &[&str]) -> (String, i32) {
+        let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+        let mut out = Vec::new();
+        let status = printf(&args, &mut out);
+        (String::from_utf8(out).unwrap(), status)
+    }
+
+    #[test]
+    fn format_reused_for_arguments() {
+        assert_eq!(format(&["%s=%d\\n", "a", "1", "b"]), ("a=1\nb=0\n".into(), 0));
+        assert_eq!(format(&["--", "%%|%c\\n", "xyz"]), ("%|x\n".into(), 0));
+    }
+
+    #[test]
+    fn integers() {
+        assert_eq!(format(&["%5d|%-5d|%05d|%+d", "42", "42", "-42", "7"]).0, "   42|42   |-0042|+7");
+        assert_eq!(format(&["%.3d|%x|%#X|%#o|%u", "5", "255", "255", "8", "-1"]).0, "005|ff|0XFF|010|18446744073709551615");
+        assert_eq!(format(&["%d %d %d", "010", "0x1f", "'A"]).0, "8 31 65");
+        assert_eq!(format(&["%ld|%hhd|%zu", "1", "2", "3"]).0, "1|2|3");
+    }
+
+    #[test]
+    fn invalid_numbers_taken_as_far_as_they_go() {
+        assert_eq!(format(&["%d|%.1f", "12abc", "1.5x"]), ("12|1.5".into(), 1));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(format(&["%.2f|%8.3f|%e", "3.14159", "-2.5", "1234.5"]).0, "3.14|  -2.500|1.234500e+03");
+        assert_eq!(format(&["%g|%g|%g|%G", "100000", "1000000", "0.0001", "1e-5"]).0, "100000|1e+06|0.0001|1E-05");
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(format(&["[%5s][%-5s][%.2s][%*s]", "ab", "ab", "abc", "3", "x"]).0, "[   ab][ab   ][ab][  x]");
+        assert_eq!(format(&["%b|%b", "a\\tb", "c\\cd"]).0, "a\tb|c");
+    }
+
+    #[test]
+    fn output_written_until_an_error() {
+        // the length modifier `q` isn't one of C99
+        assert_eq!(format(&["x%qd", "1"]), ("x".into(), 1));
+        assert_eq!(format(&["ab%"]), ("ab".into(), 1));
+    }
+}
@@ -1,4 +1,6 @@
 use std::env;
+use crate::cmd::builtin::{Builtin, Io};
+use crate::shell::ShellState;
 
 pub fn pwd(args: &[String]) -> i32 {
     if args.len() != 0 {
@@ -12,4 +14,20 @@ pub fn pwd(args: &[String]) -> i32 {
     };
 
     0
-}
\ No newline at end of file
+}
+
+pub struct Pwd;
+
+impl Builtin for Pwd {
+    fn name(&self) -> &'static str {
+        "pwd"
+    }
+
+    fn usage(&self) -> &'static str {
+        "pwd"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        pwd(args)
+    }
+}
@@ -0,0 +1,330 @@
+use libc::{ECHO, ICANON, POLLIN, SIGALRM, SIGINT, STDIN_FILENO, VMIN, VTIME, c_void, isatty, poll, pollfd};
+use std::{
+    env,
+    io::{self, ErrorKind},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    cli::editor::terminal::RawMode,
+    cmd::builtin::{Builtin, Io},
+    interpreter::interpreter::is_name,
+    shell::ShellState,
+};
+
+const USAGE: &str = "read: usage: read [-rs] [-a array] [-d delim] [-n nchars] [-p prompt] [-t timeout] [name ...]";
+
+#[derive(Default)]
+struct Options {
+    /// `-r`: backslashes are plain characters
+    raw: bool,
+    /// `-s`: the characters typed on a terminal aren't echoed
+    silent: bool,
+    /// `-p`: written to standard error before reading from a terminal
+    prompt: Option<String>,
+    /// `-t`: how long to wait for the whole input
+    timeout: Option<Duration>,
+    /// `-n`: the number of characters read at most
+    count: Option<usize>,
+    /// `-d`: the character ending the input instead of a newline
+    delimiter: Option<u8>,
+    /// `-a`: the array set to the fields read
+    array: Option<String>,
+}
+
+/// `read [-rs] [-a array] [-d delim] [-n nchars] [-p prompt] [-t timeout] [name ...]` reads a line
+/// and splits it into fields at the characters of `IFS`, each name being set to one of them and the
+/// last one to the rest of the line. Without names the line is set to `REPLY` as it is.
+///
+/// Standard input is read a byte at a time so what follows the line is left to the next commands.
+///
+/// The status is 1 at the end of input and above 128 when the timeout expired,
+/// the variables are set to what was read in both cases.
+pub fn read(state: &mut ShellState, args: &[String]) -> i32 {
+    let (options, names) = match parse_options(args) {
+        Ok(parsed) => parsed,
+        Err(status) => return status,
+    };
+    if let Some(name) = names.iter().chain(&options.array).find(|name| !is_name(name)) {
+        eprintln!("0-shell: read: `{name}': not a valid identifier");
+        return 1;
+    }
+
+    let terminal = unsafe { isatty(STDIN_FILENO) } == 1;
+
+    // `-t 0` only tells whether there is input
+    if options.timeout == Some(Duration::ZERO) {
+        return if ready(Duration::ZERO).unwrap_or(false) { 0 } else { 1 };
+    }
+
+    if terminal && let Some(prompt) = &options.prompt {
+        eprint!("{prompt}");
+    }
+    // characters are given as they are typed when the input doesn't end with a newline
+    let single = options.count.is_some() || options.delimiter.is_some();
+    let _mode = match terminal && (options.silent || single) {
+        true => RawMode::set(|mode| {
+            if options.silent {
+                mode.c_lflag &= !ECHO;
+            }
+            if single {
+                mode.c_lflag &= !ICANON;
+                mode.c_cc[VMIN] = 1;
+                mode.c_cc[VTIME] = 0;
+            }
+        })
+        .ok(),
+        false => None,
+    };
+
+    let (line, status) = read_line(&options);
+    let ifs = env::var("IFS").unwrap_or_else(|_| " \t\n".into());
+    if let Some(array) = &options.array {
+        state.set_array(array, split(&line, &ifs, usize::MAX));
+    } else if names.is_empty() {
+        state.set_var("REPLY", Some(&line.iter().map(|(ch, _)| ch).collect::<String>()));
+    } else {
+        let fields = split(&line, &ifs, names.len());
+        for (i, name) in names.iter().enumerate() {
+            state.set_var(name, Some(fields.get(i).map_or("", String::as_str)));
+        }
+    }
+    status
+}
+
+fn parse_options(args: &[String]) -> Result<(Options, &[String]), i32> {
+    let mut options = Options::default();
+    let mut i = 0;
+
+    while let Some(arg) = args.get(i) {
+        i += 1;
+        if arg == "--" {
+            break;
+        }
+        if !arg.starts_with('-') || arg.len() == 1 {
+            i -= 1;
+            break;
+        }
+
+        for (pos, flag) in arg.char_indices().skip(1) {
+            match flag {
+                'r' => options.raw = true,
+                's' => options.silent = true,
+                'a' | 'd' | 'n' | 'p' | 't' => {
+                    // the value is the rest of the argument, or the next one
+                    let rest = &arg[pos + 1..];
+                    let value = match rest.is_empty() {
+                        true => {
+                            i += 1;
+                            args.get(i - 1).cloned()
+                        }
+                        false => Some(rest.to_string()),
+                    };
+                    let Some(value) = value else {
+                        eprintln!("0-shell: read: -{flag}: option requires an argument");
+                        eprintln!("{USAGE}");
+                        return Err(2);
+                    };
+                    set_option(&mut options, flag, value)?;
+                    break;
+                }
+                _ => {
+                    eprintln!("0-shell: read: -{flag}: invalid option");
+                    eprintln!("{USAGE}");
+                    return Err(2);
+                }
+            }
+        }
+    }
+
+    Ok((options, &args[i.min(args.len())..]))
+}
+
+fn set_option(options: &mut Options, flag: char, value: String) -> Result<(), i32> {
+    match flag {
+        'a' => options.array = Some(value),
+        // an empty delimiter stands for the NUL character
+        'd' => options.delimiter = Some(value.bytes().next().unwrap_or(0)),
+        'p' => options.prompt = Some(value),
+        'n' => match value.parse() {
+            Ok(count) => options.count = Some(count),
+            Err(_) => {
+                eprintln!("0-shell: read: {value}: invalid number");
+                return Err(1);
+            }
+        },
+        _ => match value.parse::<f64>().ok().and_then(|secs| Duration::try_from_secs_f64(secs).ok()) {
+            Some(timeout) => options.timeout = Some(timeout),
+            None => {
+                eprintln!("0-shell: read: {value}: invalid timeout specification");
+                return Err(1);
+            }
+        },
+    }
+    Ok(())
+}
+
+/// Reads up to the delimiter, returns the characters read, with whether each one was escaped
+/// by a backslash, and the status of `read`.
+fn read_line(options: &Options) -> (Vec<(char, bool)>, i32) {
+    let deadline = options.timeout.map(|timeout| Instant::now() + timeout);
+    let delimiter = options.delimiter.unwrap_or(b'\n');
+    let mut bytes = Vec::new();
+    let mut escaped = Vec::new();
+    let mut backslash = false;
+    let mut count = 0;
+    // bytes left to complete the last character
+    let mut pending = 0u8;
+
+    let status = loop {
+        if pending == 0 && options.count.is_some_and(|max| count >= max) {
+            break 0;
+        }
+        let byte = match next_byte(deadline) {
+            Ok(Some(byte)) => byte,
+            Ok(None) => break 1,
+            Err(e) if e.kind() == ErrorKind::TimedOut => break 128 + SIGALRM,
+            Err(e) if e.kind() == ErrorKind::Interrupted => break 128 + SIGINT,
+            Err(e) => {
+                eprintln!("0-shell: read: read error: {e}");
+                break 1;
+            }
+        };
+
+        let quoted = backslash;
+        if backslash {
+            backslash = false;
+            // a backslash followed by a newline continues the line
+            if byte == b'\n' {
+                continue;
+            }
+        } else if byte == delimiter {
+            break 0;
+        } else if byte == 0 {
+            // variables can't hold it
+            continue;
+        } else if byte == b'\\' && !options.raw {
+            backslash = true;
+            continue;
+        }
+
+        match byte {
+            0x80..0xc0 => pending = pending.saturating_sub(1),
+            0xc0..0xe0 => pending = 1,
+            0xe0..0xf0 => pending = 2,
+            0xf0.. => pending = 3,
+            _ => pending = 0,
+        }
+        if !(0x80..0xc0).contains(&byte) {
+            count += 1;
+        }
+        escaped.push(quoted);
+        bytes.push(byte);
+    };
+
+    let line = match std::str::from_utf8(&bytes) {
+        Ok(text) => text.char_indices().map(|(i, ch)| (ch, escaped[i])).collect(),
+        Err(_) => String::from_utf8_lossy(&bytes).chars().map(|ch| (ch, false)).collect(),
+    };
+    (line, status)
+}
+
+/// Splits `line` into at most `count` fields at the characters of `ifs` that aren't escaped,
+/// the last field taking the rest of the line. The whitespace of `ifs` around fields is dropped.
+fn split(line: &[(char, bool)], ifs: &str, count: usize) -> Vec<String> {
+    let separator = |i: usize| !line[i].1 && ifs.contains(line[i].0);
+    let blank = |i: usize| separator(i) && " \t\n".contains(line[i].0);
+    let text = |chars: &[(char, bool)]| chars.iter().map(|(ch, _)| ch).collect::<String>();
+
+    let mut fields = Vec::new();
+    let mut i = 0;
+    while i < line.len() && blank(i) {
+        i += 1;
+    }
+
+    while i < line.len() {
+        if fields.len() + 1 == count {
+            let mut end = line.len();
+            while end > i && blank(end - 1) {
+                end -= 1;
+            }
+            fields.push(text(&line[i..end]));
+            break;
+        }
+
+        let start = i;
+        while i < line.len() && !separator(i) {
+            i += 1;
+        }
+        fields.push(text(&line[start..i]));
+
+        // a separator is made of whitespace around at most one other character of `ifs`
+        while i < line.len() && blank(i) {
+            i += 1;
+        }
+        if i < line.len() && separator(i) {
+            i += 1;
+            while i < line.len() && blank(i) {
+                i += 1;
+            }
+        }
+    }
+    fields
+}
+
+/// Reads a byte of standard input, `None` at the end of input and an error of kind `TimedOut`
+/// past `deadline`.
+fn next_byte(deadline: Option<Instant>) -> io::Result<Option<u8>> {
+    // a file always has input ready, the time left is checked as well
+    if let Some(deadline) = deadline {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() || !ready(left)? {
+            return Err(ErrorKind::TimedOut.into());
+        }
+    }
+
+    let mut byte = 0u8;
+    match unsafe { libc::read(STDIN_FILENO, &mut byte as *mut u8 as *mut c_void, 1) } {
+        -1 => Err(io::Error::last_os_error()),
+        0 => Ok(None),
+        _ => Ok(Some(byte)),
+    }
+}
+
+/// whether standard input can be read within `timeout`
+fn ready(timeout: Duration) -> io::Result<bool> {
+    let mut fds = pollfd {
+        fd: STDIN_FILENO,
+        events: POLLIN,
+        revents: 0,
+    };
+    let millis = timeout.as_millis().min(i32::MAX as u128) as i32;
+    match unsafe { poll(&mut fds, 1, millis) } {
+        -1 => Err(io::Error::last_os_error()),
+        n => Ok(n > 0),
+    }
+}
+
+pub struct Read;
+
+impl Builtin for Read {
+    fn name(&self) -> &'static str {
+        "read"
+    }
+
+    fn usage(&self) -> &'static str {
+        "read [-rs] [-a array] [-d delim] [-n nchars] [-p prompt] [-t timeout] [name ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-a", "-d", "-n", "-p", "-r", "-s", "-t"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        read(state, args)
+    }
+}
@@ -1,5 +1,7 @@
 use std::{env, fs, path::PathBuf};
 use crate::utils::error::clear_error;
+use crate::cmd::builtin::{Builtin, Io};
+use crate::shell::ShellState;
 
 pub fn rm(mut args: &[String]) -> i32 {
     if args.is_empty() {
@@ -95,4 +97,24 @@ pub fn rm(mut args: &[String]) -> i32 {
     }
 
     0
-}
\ No newline at end of file
+}
+
+pub struct Rm;
+
+impl Builtin for Rm {
+    fn name(&self) -> &'static str {
+        "rm"
+    }
+
+    fn usage(&self) -> &'static str {
+        "rm [-r] file ..."
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-r"]
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        rm(args)
+    }
+}
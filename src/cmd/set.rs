@@ -0,0 +1,102 @@
+use std::env;
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{
+        ShellState,
+        options::{OPTIONS, option_name},
+    },
+    utils::quote::quote,
+};
+
+const USAGE: &str = "set: usage: set [-+efuvxC] [-+o option] [--] [arg ...]";
+
+/// `set [-+efuvxC] [-+o option] [--] [arg ...]` sets (`-`) or unsets (`+`) shell options,
+/// the remaining arguments replace the positional parameters.
+///
+/// `set -o` lists the options, `set +o` prints the commands restoring them,
+/// and `set` alone lists the variables.
+pub fn set(state: &mut ShellState, args: &[String]) -> i32 {
+    if args.is_empty() {
+        let mut vars: Vec<(String, String)> = env::vars().collect();
+        vars.sort();
+        for (name, value) in vars {
+            println!("{name}={}", quote(&value));
+        }
+        return 0;
+    }
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let on = match arg.chars().next() {
+            _ if arg == "--" || arg == "-" => {
+                state.positional = args.cloned().collect();
+                return 0;
+            }
+            Some('-') => true,
+            Some('+') => false,
+            _ => {
+                state.positional = std::iter::once(arg).chain(args).cloned().collect();
+                return 0;
+            }
+        };
+
+        for flag in arg[1..].chars() {
+            if flag == 'o' {
+                let Some(name) = args.next() else {
+                    list(state, on);
+                    continue;
+                };
+                if !state.set_option(name, on) {
+                    eprintln!("0-shell: set: {name}: invalid option name");
+                    return 1;
+                }
+                continue;
+            }
+
+            let Some(name) = option_name(flag) else {
+                eprintln!("0-shell: set: {}{flag}: invalid option", if on { '-' } else { '+' });
+                eprintln!("{USAGE}");
+                return 2;
+            };
+            state.set_option(name, on);
+        }
+    }
+
+    0
+}
+
+/// `set -o` shows whether each option is on, `set +o` prints the commands setting them back
+fn list(state: &ShellState, readable: bool) {
+    for (name, _) in OPTIONS {
+        let on = state.options.get(name) == Some(true);
+        match readable {
+            true => println!("{name:<15}\t{}", if on { "on" } else { "off" }),
+            false => println!("set {}o {name}", if on { '-' } else { '+' }),
+        }
+    }
+}
+
+pub struct Set;
+
+impl Builtin for Set {
+    fn name(&self) -> &'static str {
+        "set"
+    }
+
+    fn usage(&self) -> &'static str {
+        "set [-+efuvxC] [-+o option] [--] [arg ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-C", "-e", "-f", "-o", "-u", "-v", "-x"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        set(state, args)
+    }
+}
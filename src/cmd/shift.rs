@@ -0,0 +1,51 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+};
+
+/// `shift [n]` drops the first `n` positional parameters, 1 by default,
+/// the following ones taking their place. Nothing is dropped when there are fewer than `n`.
+pub fn shift(state: &mut ShellState, args: &[String]) -> i32 {
+    let count = match args {
+        [] => 1,
+        [n] => match n.trim().parse::<i64>() {
+            Ok(n) => n,
+            Err(_) => {
+                eprintln!("0-shell: shift: {n}: numeric argument required");
+                return 1;
+            }
+        },
+        _ => {
+            eprintln!("0-shell: shift: too many arguments");
+            return 1;
+        }
+    };
+
+    match usize::try_from(count) {
+        Ok(count) if count <= state.positional.len() => {
+            state.positional.drain(..count);
+            0
+        }
+        _ => 1,
+    }
+}
+
+pub struct Shift;
+
+impl Builtin for Shift {
+    fn name(&self) -> &'static str {
+        "shift"
+    }
+
+    fn usage(&self) -> &'static str {
+        "shift [n]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        shift(state, args)
+    }
+}
@@ -0,0 +1,264 @@
+use libc::{R_OK, W_OK, X_OK, access, c_int, isatty};
+use std::{
+    ffi::CString,
+    fs::{self, Metadata},
+    os::unix::fs::MetadataExt,
+    time::SystemTime,
+};
+
+use super::{
+    builtin::{Builtin, Io},
+    ls::entry::{Entry, FileType},
+};
+use crate::shell::ShellState;
+
+/// operators taking a single operand
+pub const UNARY: &[&str] = &[
+    "-b", "-c", "-d", "-e", "-f", "-h", "-L", "-n", "-p", "-r", "-s", "-S", "-t", "-w", "-x", "-z",
+];
+
+/// operators between two operands, `-a` and `-o` join expressions when there are more than three arguments
+pub const BINARY: &[&str] = &[
+    "=", "==", "!=", "<", ">", "-eq", "-ne", "-lt", "-le", "-gt", "-ge", "-nt", "-ot", "-ef", "-a", "-o",
+];
+
+/// `test expr` evaluates a conditional expression,
+/// the status is 0 when it's true, 1 when it's false and 2 on an error.
+pub fn test(args: &[String]) -> i32 {
+    run("test", args)
+}
+
+/// `[ expr ]` is `test expr`, with a closing `]`.
+pub fn bracket(args: &[String]) -> i32 {
+    match args.split_last() {
+        Some((last, args)) if last == "]" => run("[", args),
+        _ => {
+            eprintln!("0-shell: [: missing `]'");
+            2
+        }
+    }
+}
+
+fn run(name: &str, args: &[String]) -> i32 {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    match evaluate(&args) {
+        Ok(true) => 0,
+        Ok(false) => 1,
+        Err(e) => {
+            eprintln!("0-shell: {name}: {e}");
+            2
+        }
+    }
+}
+
+/// Evaluates `args` following the POSIX rules on their number, which decide how `!` and parentheses
+/// are read with up to four arguments, longer expressions are parsed with precedence.
+/// https://pubs.opengroup.org/onlinepubs/9699919799/utilities/test.html
+fn evaluate(args: &[&str]) -> Result<bool, String> {
+    match args {
+        [] => Ok(false),
+        [arg] => Ok(!arg.is_empty()),
+        ["!", arg] => Ok(arg.is_empty()),
+        [op, operand] => unary(op, operand),
+        [left, op, right] if BINARY.contains(op) => binary(left, op, right),
+        ["!", rest @ ..] if rest.len() <= 3 => evaluate(rest).map(|result| !result),
+        ["(", inner @ .., ")"] if inner.len() <= 2 => evaluate(inner),
+        _ => {
+            let mut expression = Expression { args, pos: 0 };
+            let result = expression.or()?;
+            match expression.args.get(expression.pos) {
+                // a word followed by something else than an operator
+                Some(arg) if expression.pos == 1 => Err(format!("{arg}: binary operator expected")),
+                Some(_) => Err("too many arguments".into()),
+                None => Ok(result),
+            }
+        }
+    }
+}
+
+/// an expression of more than four arguments, with `-o` binding looser than `-a`, and `-a` than `!`
+struct Expression<'a> {
+    args: &'a [&'a str],
+    pos: usize,
+}
+
+impl<'a> Expression<'a> {
+    fn next(&mut self) -> Option<&'a str> {
+        let arg = self.args.get(self.pos).copied();
+        self.pos += 1;
+        arg
+    }
+
+    fn peek(&self, offset: usize) -> Option<&'a str> {
+        self.args.get(self.pos + offset).copied()
+    }
+
+    fn or(&mut self) -> Result<bool, String> {
+        let mut result = self.and()?;
+        while self.peek(0) == Some("-o") {
+            self.pos += 1;
+            // both sides are evaluated, so errors are reported wherever they are
+            result |= self.and()?;
+        }
+        Ok(result)
+    }
+
+    fn and(&mut self) -> Result<bool, String> {
+        let mut result = self.not()?;
+        while self.peek(0) == Some("-a") {
+            self.pos += 1;
+            result &= self.not()?;
+        }
+        Ok(result)
+    }
+
+    fn not(&mut self) -> Result<bool, String> {
+        if self.peek(0) == Some("!") && self.peek(1).is_some() {
+            self.pos += 1;
+            return self.not().map(|result| !result);
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<bool, String> {
+        let Some(arg) = self.next() else {
+            return Err("argument expected".into());
+        };
+
+        if arg == "(" {
+            let result = self.or()?;
+            return match self.next() {
+                Some(")") => Ok(result),
+                _ => Err("`)' expected".into()),
+            };
+        }
+
+        if let Some(op) = self.peek(0)
+            && BINARY.contains(&op)
+            && !matches!(op, "-a" | "-o")
+            && let Some(right) = self.peek(1)
+        {
+            self.pos += 2;
+            return binary(arg, op, right);
+        }
+
+        if UNARY.contains(&arg)
+            && let Some(operand) = self.next()
+        {
+            return unary(arg, operand);
+        }
+
+        Ok(!arg.is_empty())
+    }
+}
+
+/// Evaluates the unary operator `op`, an error for an unknown one.
+pub fn unary(op: &str, operand: &str) -> Result<bool, String> {
+    let metadata = match op {
+        "-h" | "-L" => fs::symlink_metadata(operand),
+        _ => fs::metadata(operand),
+    };
+    let is = |file_type: FileType| metadata.as_ref().is_ok_and(|m| Entry::get_entry_type(m).0 == file_type);
+
+    let result = match op {
+        "-z" => operand.is_empty(),
+        "-n" => !operand.is_empty(),
+        "-t" => unsafe { isatty(integer(operand)? as c_int) == 1 },
+        "-e" => metadata.is_ok(),
+        "-f" => is(FileType::Regular) || is(FileType::Executable),
+        "-d" => is(FileType::Directory),
+        "-h" | "-L" => is(FileType::Symlink),
+        "-p" => is(FileType::NamedPipe),
+        "-S" => is(FileType::Socket),
+        "-b" => is(FileType::BlockDevice),
+        "-c" => is(FileType::CharDevice),
+        "-s" => metadata.is_ok_and(|m| m.len() > 0),
+        "-r" => accessible(operand, R_OK),
+        "-w" => accessible(operand, W_OK),
+        "-x" => accessible(operand, X_OK),
+        _ => return Err(format!("{op}: unary operator expected")),
+    };
+    Ok(result)
+}
+
+/// Evaluates the binary operator `op`, an error for an unknown one.
+pub fn binary(left: &str, op: &str, right: &str) -> Result<bool, String> {
+    let result = match op {
+        "=" | "==" => left == right,
+        "!=" => left != right,
+        "<" => left < right,
+        ">" => left > right,
+        "-eq" => integer(left)? == integer(right)?,
+        "-ne" => integer(left)? != integer(right)?,
+        "-lt" => integer(left)? < integer(right)?,
+        "-le" => integer(left)? <= integer(right)?,
+        "-gt" => integer(left)? > integer(right)?,
+        "-ge" => integer(left)? >= integer(right)?,
+        // a file that exists is newer than one that doesn't
+        "-nt" => match (modified(left), modified(right)) {
+            (Some(left), Some(right)) => left > right,
+            (left, right) => left.is_some() && right.is_none(),
+        },
+        "-ot" => match (modified(left), modified(right)) {
+            (Some(left), Some(right)) => left < right,
+            (left, right) => left.is_none() && right.is_some(),
+        },
+        "-ef" => match (fs::metadata(left), fs::metadata(right)) {
+            (Ok(left), Ok(right)) => same_file(&left, &right),
+            _ => false,
+        },
+        "-a" => !left.is_empty() && !right.is_empty(),
+        "-o" => !left.is_empty() || !right.is_empty(),
+        _ => return Err(format!("{op}: binary operator expected")),
+    };
+    Ok(result)
+}
+
+fn integer(arg: &str) -> Result<i64, String> {
+    arg.trim().parse().map_err(|_| format!("{arg}: integer expression expected"))
+}
+
+/// whether the shell may access `path` for reading, writing or executing
+fn accessible(path: &str, mode: c_int) -> bool {
+    CString::new(path).is_ok_and(|path| unsafe { access(path.as_ptr(), mode) == 0 })
+}
+
+fn modified(path: &str) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+fn same_file(left: &Metadata, right: &Metadata) -> bool {
+    left.dev() == right.dev() && left.ino() == right.ino()
+}
+
+pub struct Test;
+
+impl Builtin for Test {
+    fn name(&self) -> &'static str {
+        "test"
+    }
+
+    fn usage(&self) -> &'static str {
+        "test [expr]"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        test(args)
+    }
+}
+
+pub struct Bracket;
+
+impl Builtin for Bracket {
+    fn name(&self) -> &'static str {
+        "["
+    }
+
+    fn usage(&self) -> &'static str {
+        "[ arg... ]"
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        bracket(args)
+    }
+}
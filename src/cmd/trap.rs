@@ -0,0 +1,119 @@
+use libc::c_int;
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{
+        ShellState,
+        signals::{SIGNALS, signal_number, signal_to_name},
+    },
+};
+
+/// `trap [action] condition...` sets the commands run when the shell receives a signal.
+///
+/// An empty action ignores the signals, `-` restores their default disposition,
+/// `EXIT` (or `0`) is run when the shell exits.
+/// `trap` and `trap -p [condition...]` print the traps, `trap -l` lists the signal names.
+pub fn trap(state: &mut ShellState, args: &[String]) -> i32 {
+    let args = match args.first().map(String::as_str) {
+        Some("--") => &args[1..],
+        _ => args,
+    };
+
+    match args.first().map(String::as_str) {
+        None => return print_traps(state, &[]),
+        Some("-p") => return print_traps(state, &args[1..]),
+        Some("-l") => {
+            for (sig, name) in SIGNALS {
+                println!("{sig:>2}) SIG{name}");
+            }
+            return 0;
+        }
+        Some(opt) if opt.starts_with('-') && opt != "-" => {
+            eprintln!("0-shell: trap: {opt}: invalid option");
+            eprintln!("trap: usage: trap [-lp] [[arg] signal_spec ...]");
+            return 2;
+        }
+        _ => {}
+    }
+
+    // a single operand, or a first operand that is a signal number, resets the conditions
+    let (action, conditions) = match args[0].as_str() {
+        "-" => (None, &args[1..]),
+        first if args.len() == 1 || first.parse::<u32>().is_ok() => (None, args),
+        first => (Some(first.to_string()), &args[1..]),
+    };
+
+    let mut exit_status = 0;
+    for condition in conditions {
+        let Some(sig) = condition_number(condition) else {
+            eprintln!("0-shell: trap: {condition}: invalid signal specification");
+            exit_status = 1;
+            continue;
+        };
+        state.set_trap(sig, action.clone());
+    }
+
+    exit_status
+}
+
+/// converts `EXIT`, `0` or a signal name or number into a condition number
+fn condition_number(condition: &str) -> Option<c_int> {
+    match condition.to_ascii_uppercase().as_str() {
+        "EXIT" | "SIGEXIT" | "0" => Some(0),
+        _ => signal_number(condition),
+    }
+}
+
+fn condition_name(sig: c_int) -> String {
+    match sig {
+        0 => "EXIT".into(),
+        _ => signal_to_name(sig).map_or(sig.to_string(), |name| format!("SIG{name}")),
+    }
+}
+
+/// prints the traps in a form that can be read back by the shell
+fn print_traps(state: &ShellState, conditions: &[String]) -> i32 {
+    let mut exit_status = 0;
+    let mut signals = Vec::new();
+    for condition in conditions {
+        match condition_number(condition) {
+            Some(sig) => signals.push(sig),
+            None => {
+                eprintln!("0-shell: trap: {condition}: invalid signal specification");
+                exit_status = 1;
+            }
+        }
+    }
+
+    for (sig, action) in &state.traps {
+        if signals.is_empty() || signals.contains(sig) {
+            println!("trap -- '{}' {}", action.replace('\'', "'\\''"), condition_name(*sig));
+        }
+    }
+
+    exit_status
+}
+
+pub struct Trap;
+
+impl Builtin for Trap {
+    fn name(&self) -> &'static str {
+        "trap"
+    }
+
+    fn usage(&self) -> &'static str {
+        "trap [-lp] [[arg] signal_spec ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-l", "-p"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        trap(state, args)
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{
+    cmd::{
+        builtin::{Builtin, Io},
+        command::{describe, resolve},
+    },
+    shell::ShellState,
+};
+
+const USAGE: &str = "type: usage: type [-at] name [name ...]";
+
+/// `type [-at] name...` tells what each name stands for: an alias, a keyword, a function,
+/// a builtin or an executable file. `-a` lists everything it stands for, in the order the shell
+/// looks them up, `-t` prints a single word for each. The status is 1 when a name isn't found.
+pub fn type_(state: &ShellState, args: &[String]) -> i32 {
+    let mut all = false;
+    let mut short = false;
+    let mut i = 0;
+    while let Some(arg) = args.get(i).filter(|arg| arg.starts_with('-') && arg.len() > 1) {
+        i += 1;
+        if arg == "--" {
+            break;
+        }
+        for flag in arg.chars().skip(1) {
+            match flag {
+                'a' => all = true,
+                't' => short = true,
+                _ => {
+                    eprintln!("0-shell: type: -{flag}: invalid option");
+                    eprintln!("{USAGE}");
+                    return 2;
+                }
+            }
+        }
+    }
+
+    let mut status = 0;
+    for name in &args[i..] {
+        let found = resolve(state, name, all);
+        if found.is_empty() {
+            if !short {
+                eprintln!("0-shell: type: {name}: not found");
+            }
+            status = 1;
+        }
+        for resolution in &found {
+            match short {
+                true => println!("{}", resolution.kind()),
+                false => println!("{}", describe(name, resolution)),
+            }
+        }
+    }
+    status
+}
+
+pub struct Type;
+
+impl Builtin for Type {
+    fn name(&self) -> &'static str {
+        "type"
+    }
+
+    fn usage(&self) -> &'static str {
+        "type [-at] name [name ...]"
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-a", "-t"]
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        type_(state, args)
+    }
+}
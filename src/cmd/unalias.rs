@@ -0,0 +1,57 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{ShellState, aliases::with_aliases},
+};
+
+/// `unalias [-a] name ...` removes the aliases named, or all of them with `-a`.
+pub fn unalias(args: &[String]) -> i32 {
+    match args.first().map(String::as_str) {
+        Some("-a") => {
+            with_aliases(|aliases| aliases.clear());
+            return 0;
+        }
+        Some(arg) if arg.starts_with('-') && arg != "--" => {
+            eprintln!("0-shell: unalias: {arg}: invalid option");
+            eprintln!("unalias: usage: unalias [-a] name [name ...]");
+            return 2;
+        }
+        None => {
+            eprintln!("unalias: usage: unalias [-a] name [name ...]");
+            return 2;
+        }
+        _ => {}
+    }
+
+    let mut status = 0;
+    for name in args.iter().skip_while(|arg| *arg == "--") {
+        if with_aliases(|aliases| aliases.remove(name)).is_none() {
+            eprintln!("0-shell: unalias: {name}: not found");
+            status = 1;
+        }
+    }
+    status
+}
+
+pub struct Unalias;
+
+impl Builtin for Unalias {
+    fn name(&self) -> &'static str {
+        "unalias"
+    }
+
+    fn usage(&self) -> &'static str {
+        "unalias [-a] name [name ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-a"]
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        unalias(args)
+    }
+}
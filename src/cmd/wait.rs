@@ -0,0 +1,71 @@
+use libc::pid_t;
+
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::ShellState,
+};
+
+/// `wait [pid|%job]...` waits for background jobs to finish and returns the status of the last one,
+/// without operands it waits for every known job and returns 0.
+pub fn wait(state: &mut ShellState, args: &[String]) -> i32 {
+    if args.is_empty() {
+        let ids: Vec<usize> = state.jobs.iter().map(|job| job.id).collect();
+        for id in ids {
+            state.jobs.wait(id);
+        }
+        return 0;
+    }
+
+    let mut exit_status = 0;
+    for arg in args {
+        let id = if arg.starts_with('%') {
+            match state.jobs.find(arg) {
+                Ok(job) => job.id,
+                Err(e) => {
+                    eprintln!("0-shell: wait: {e}");
+                    exit_status = 127;
+                    continue;
+                }
+            }
+        } else {
+            let Ok(pid) = arg.parse::<pid_t>() else {
+                eprintln!("0-shell: wait: `{arg}': not a pid or valid job spec");
+                exit_status = 2;
+                continue;
+            };
+
+            match state.jobs.by_pid(pid) {
+                Some(job) => job.id,
+                None => {
+                    eprintln!("0-shell: wait: pid {pid} is not a child of this shell");
+                    exit_status = 127;
+                    continue;
+                }
+            }
+        };
+
+        exit_status = state.jobs.wait(id);
+    }
+
+    exit_status
+}
+
+pub struct Wait;
+
+impl Builtin for Wait {
+    fn name(&self) -> &'static str {
+        "wait"
+    }
+
+    fn usage(&self) -> &'static str {
+        "wait [id ...]"
+    }
+
+    fn parent(&self) -> bool {
+        true
+    }
+
+    fn run(&self, state: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        wait(state, args)
+    }
+}
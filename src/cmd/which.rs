@@ -0,0 +1,55 @@
+use crate::{
+    cmd::builtin::{Builtin, Io},
+    shell::{ShellState, path},
+};
+
+/// `which [-a] name...` prints the path of the executable each name runs from `PATH`,
+/// or of all of them with `-a`. Aliases, functions and builtins aren't looked up,
+/// `type` tells about them. The status is 1 when a name isn't found.
+pub fn which(args: &[String]) -> i32 {
+    let (all, names) = match args.first().map(String::as_str) {
+        Some("-a") => (true, &args[1..]),
+        Some("--") => (false, &args[1..]),
+        Some(option) if option.starts_with('-') && option.len() > 1 => {
+            eprintln!("0-shell: which: {option}: invalid option");
+            eprintln!("which: usage: which [-a] name [name ...]");
+            return 2;
+        }
+        _ => (false, args),
+    };
+
+    let mut status = 0;
+    for name in names {
+        let found = match all {
+            true => path::search(name),
+            false => path::find(name).into_iter().collect(),
+        };
+        if found.is_empty() {
+            status = 1;
+        }
+        for path in found {
+            println!("{}", path.display());
+        }
+    }
+    status
+}
+
+pub struct Which;
+
+impl Builtin for Which {
+    fn name(&self) -> &'static str {
+        "which"
+    }
+
+    fn usage(&self) -> &'static str {
+        "which [-a] name [name ...]"
+    }
+
+    fn options(&self) -> &'static [&'static str] {
+        &["-a"]
+    }
+
+    fn run(&self, _: &mut ShellState, args: &[String], _: &mut Io) -> i32 {
+        which(args)
+    }
+}
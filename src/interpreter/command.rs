@@ -1,113 +1,226 @@
 use crate::{
-    interpreter::tokens::RedirectionKind::{self, *},
+    interpreter::tokens::{
+        Operator,
+        RedirectionKind::{self, *},
+    },
     utils::error::StrError,
 };
-use libc::{STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, dup, dup2};
+use libc::{
+    F_DUPFD_CLOEXEC, STDERR_FILENO, STDIN_FILENO, STDOUT_FILENO, _exit, close, dup2, fcntl, fork, pid_t, waitpid,
+};
 use std::{
-    fs::{File, OpenOptions},
-    io::{Error, Read, Write, pipe},
-    os::fd::{AsRawFd, FromRawFd},
-    process::exit,
-    thread::{JoinHandle, spawn},
+    fs::{self, File, OpenOptions},
+    io::{self, Error, Read, Write, pipe},
+    os::fd::{AsRawFd, FromRawFd, OwnedFd, RawFd},
+    path::PathBuf,
+    ptr,
 };
 
+/// The files the descriptors of a command are redirected to.
+#[derive(Default)]
 pub struct IoStreams {
-    pub stdin: Vec<Box<dyn Send + Read>>,
-    pub stdout: Vec<Box<dyn Send + Write>>,
-    pub stderr: Vec<Box<dyn Send + Write>>,
+    pub redirections: Vec<Redirection>,
+}
+
+/// The files a descriptor is redirected to, in the order of the redirections:
+/// each output file gets a copy of what's written, input files are read one after the other.
+pub struct Redirection {
+    pub fd: RawFd,
+    pub input: bool,
+    pub files: Vec<File>,
 }
 
+/// Redirections in effect, which can be undone.
+pub struct Redirected {
+    /// the descriptors replaced, with copies of those that were open to put them back
+    saved: Vec<(RawFd, Option<OwnedFd>)>,
+    /// the processes copying between a descriptor and its files, when there are several
+    pub copiers: Vec<(RawFd, pid_t)>,
+}
+
+#[derive(Default)]
 pub struct Command {
     pub name: String,
     pub args: Vec<String>,
+    /// the `name=value` words before the name, which assign variables
+    pub assignments: Vec<(String, String)>,
     pub io_streams: IoStreams,
     pub error: Option<Error>,
+    /// the control operator that ended the command, if any
+    pub terminator: Option<Operator>,
+    /// set when a word of the command has a command substitution
+    pub substituted: bool,
+    /// set by `command`: the name isn't looked up among the functions
+    pub skip_functions: bool,
+    /// the executable the name was found to run in `PATH`
+    pub program: Option<PathBuf>,
 }
+
 impl IoStreams {
-    pub fn redirect(self) -> Vec<JoinHandle<()>> {
-        let IoStreams {
-            stdout,
-            stderr,
-            stdin,
-        } = self;
-        let mut handlers = Vec::new();
-        for (mut redirections, io_stream) in [(stdout, STDOUT_FILENO), (stderr, STDERR_FILENO)] {
-            if redirections.len() == 0 {
-                continue;
+    /// Adds `file` to the redirections of `fd`, replacing those in the other direction.
+    pub fn push(&mut self, fd: RawFd, input: bool, file: File) -> io::Result<()> {
+        // the file is kept clear of the descriptors it may be made to replace
+        let file = File::from(above(file.into())?);
+        match self.redirections.iter_mut().find(|r| r.fd == fd) {
+            Some(r) if r.input == input => r.files.push(file),
+            Some(r) => {
+                r.input = input;
+                r.files = vec![file];
             }
+            None => self.redirections.push(Redirection { fd, input, files: vec![file] }),
+        }
+        Ok(())
+    }
 
-            let (mut reader, writer) = match pipe() {
-                Ok(p) => p,
-                Err(e) => {
-                    eprintln!("pipe failed: {e}");
-                    exit(1);
-                }
-            };
+    /// Makes the descriptors refer to their files, through a process copying between them
+    /// when there are several.
+    pub fn apply(self) -> io::Result<Redirected> {
+        // what the shell wrote before goes where it was meant to
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
 
-            if unsafe { dup2(writer.as_raw_fd(), io_stream) } == -1 {
-                eprintln!("dup2 failed: {}", std::io::Error::last_os_error());
-                exit(1);
+        let targets: Vec<RawFd> = self.redirections.iter().map(|r| r.fd).collect();
+        let mut redirected = Redirected {
+            saved: Vec::new(),
+            copiers: Vec::new(),
+        };
+        for Redirection { fd, input, mut files } in self.redirections {
+            let saved = match unsafe { fcntl(fd, F_DUPFD_CLOEXEC, 10) } {
+                -1 => None,
+                copy => Some(unsafe { OwnedFd::from_raw_fd(copy) }),
             };
+            redirected.saved.push((fd, saved));
 
-            let handler = spawn(move || {
-                let mut buf = [0; 1024];
-                while let Ok(n) = reader.read(&mut buf) {
-                    if n == 0 {
-                        break;
+            let source = match files.len() {
+                1 => files.pop().map(OwnedFd::from),
+                _ => match copier(input, files, &targets) {
+                    Ok((end, pid)) => {
+                        redirected.copiers.push((fd, pid));
+                        Some(end)
                     }
-                    for file in &mut redirections {
-                        //todo write until n has been written
-                        let _ = file.write(&buf[..n]);
+                    Err(e) => {
+                        redirected.restore();
+                        return Err(e);
                     }
-                }
-            });
-
-            handlers.push(handler);
+                },
+            };
+            if let Some(source) = source
+                && unsafe { dup2(source.as_raw_fd(), fd) } == -1
+            {
+                let e = Error::last_os_error();
+                redirected.restore();
+                return Err(e);
+            }
         }
+        Ok(redirected)
+    }
+}
 
-        if stdin.len() == 0 {
-            return handlers;
+impl Redirected {
+    /// Puts the descriptors back as they were, then waits for the copies to be over.
+    pub fn restore(self) {
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+        for (fd, saved) in self.saved.into_iter().rev() {
+            match saved {
+                Some(saved) => unsafe { dup2(saved.as_raw_fd(), fd) },
+                None => unsafe { close(fd) },
+            };
+        }
+        for (_, pid) in self.copiers {
+            unsafe { waitpid(pid, ptr::null_mut(), 0) };
         }
-        handlers.push(Self::redirect_in(stdin));
+    }
 
-        return handlers;
+    /// Leaves the descriptors redirected, returns the processes copying to their files.
+    pub fn keep(self) -> Vec<(RawFd, pid_t)> {
+        self.copiers
     }
-    pub fn redirect_in(mut stdin: Vec<Box<dyn Read + Send>>) -> JoinHandle<()> {
-        let (reader, mut writer) = match pipe() {
-            Ok(p) => p,
-            Err(e) => {
-                eprintln!("pipe failed: {e}");
-                exit(1);
-            }
-        };
+}
 
-        if unsafe { dup2(reader.as_raw_fd(), STDIN_FILENO) } == -1 {
-            eprintln!("dup2 failed: {}", std::io::Error::last_os_error());
-            exit(1);
-        };
+/// Closes the descriptors the processes copy from or to, then waits for them to be done.
+pub fn finish_copies(copiers: &[(RawFd, pid_t)]) {
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+    for (fd, _) in copiers {
+        unsafe { close(*fd) };
+    }
+    for (_, pid) in copiers {
+        unsafe { waitpid(*pid, ptr::null_mut(), 0) };
+    }
+}
 
-        let handler = spawn(move || {
-            let mut buf = [0; 1024];
-            for file in &mut stdin {
-                while let Ok(n) = file.read(&mut buf) {
-                    if n == 0 {
-                        break;
+/// a copy of `fd` numbered 10 or above, which commands don't redirect as a rule
+fn above(fd: OwnedFd) -> io::Result<OwnedFd> {
+    match unsafe { fcntl(fd.as_raw_fd(), F_DUPFD_CLOEXEC, 10) } {
+        -1 => Err(Error::last_os_error()),
+        copy => Ok(unsafe { OwnedFd::from_raw_fd(copy) }),
+    }
+}
+
+/// Forks a process writing what's written to the returned descriptor to each of `files`,
+/// or for an `input` writing the contents of the files one after the other to it.
+fn copier(input: bool, mut files: Vec<File>, targets: &[RawFd]) -> io::Result<(OwnedFd, pid_t)> {
+    let (reader, writer) = pipe()?;
+    let (reader, writer) = (above(reader.into())?, above(writer.into())?);
+
+    let pid = unsafe { fork() };
+    if pid == -1 {
+        return Err(Error::last_os_error());
+    }
+
+    if pid == 0 {
+        // only the pipe and the files stay open, the copy ends with the writes to the pipe
+        for fd in [STDIN_FILENO, STDOUT_FILENO, STDERR_FILENO].iter().chain(targets) {
+            unsafe { close(*fd) };
+        }
+        let copied = if input {
+            drop(reader);
+            let mut writer = File::from(writer);
+            files.iter_mut().try_for_each(|file| io::copy(file, &mut writer).map(drop))
+        } else {
+            drop(writer);
+            let mut reader = File::from(reader);
+            let mut buf = [0; 4096];
+            loop {
+                match reader.read(&mut buf) {
+                    Ok(0) => break Ok(()),
+                    Ok(n) => {
+                        for file in &mut files {
+                            let _ = file.write_all(&buf[..n]);
+                        }
                     }
-                    //todo write until n has been written
-                    let _ = writer.write(&buf[..n]);
+                    Err(e) => break Err(e),
                 }
             }
-        });
-
-        return handler;
+        };
+        // the buffers of the shell aren't flushed a second time
+        unsafe { _exit(copied.is_err() as i32) };
     }
+
+    Ok(if input { (reader, pid) } else { (writer, pid) })
 }
 
 impl Command {
-    pub fn handle_redirection(&mut self, r: RedirectionKind, opperand: String) {
-        let io_streams = &mut self.io_streams;
+    /// the words of the command joined back together, used to describe jobs
+    pub fn text(&self) -> String {
+        let mut words = vec![self.name.as_str()];
+        words.extend(self.args.iter().map(String::as_str));
+        words.join(" ")
+    }
 
+    /// Opens the file of a redirection of `fd`, or of the descriptor `r` redirects by default,
+    /// with `noclobber` an existing regular file is only overwritten by `>|`.
+    pub fn handle_redirection(&mut self, r: RedirectionKind, fd: Option<RawFd>, opperand: String, noclobber: bool) {
         let file_name = opperand;
+        if noclobber
+            && matches!(r, Output | RedirectionKind::Error | OutputError)
+            && fs::metadata(&file_name).is_ok_and(|meta| meta.is_file())
+        {
+            self.error = Some(Error::other(format!("0-shell: {file_name}: cannot overwrite existing file\n")));
+            return;
+        }
+
         let mut opts = OpenOptions::new();
         if let Input = r {
             opts.read(true);
@@ -115,38 +228,17 @@ impl Command {
             opts.create(true).write(true).truncate(true);
         }
 
-        let file = opts.open(file_name);
-        if let Err(e) = file {
-            self.error = Some(Error::new(e.kind(), format!("shell: test*: {}\n", e.str())));
-            return;
-        }
-
-        let file = file.unwrap();
-        match r {
-            Input => io_streams.stdin.push(Box::new(file)),
-            Output => io_streams.stdout.push(Box::new(file)),
-            RedirectionKind::Error => io_streams.stderr.push(Box::new(file)),
-            OutputError => {
-                unsafe {
-                    let fd = dup(file.as_raw_fd());
-                    io_streams.stderr.push(Box::new(file));
-                    io_streams.stdout.push(Box::new(File::from_raw_fd(fd)));
-                };
+        let pushed = opts.open(&file_name).and_then(|file| match (r, fd) {
+            (Input, fd) => self.io_streams.push(fd.unwrap_or(STDIN_FILENO), true, file),
+            (Output | Clobber, fd) => self.io_streams.push(fd.unwrap_or(STDOUT_FILENO), false, file),
+            (RedirectionKind::Error, _) => self.io_streams.push(STDERR_FILENO, false, file),
+            (OutputError, _) => {
+                self.io_streams.push(STDOUT_FILENO, false, file.try_clone()?)?;
+                self.io_streams.push(STDERR_FILENO, false, file)
             }
-        }
-    }
-}
-impl Default for Command {
-    fn default() -> Self {
-        Self {
-            name: String::new(),
-            args: Vec::new(),
-            io_streams: IoStreams {
-                stdin: Vec::new(),
-                stdout: Vec::new(),
-                stderr: Vec::new(),
-            },
-            error: None,
+        });
+        if let Err(e) = pushed {
+            self.error = Some(Error::new(e.kind(), format!("0-shell: {file_name}: {}\n", e.str())));
         }
     }
 }
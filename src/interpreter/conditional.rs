@@ -0,0 +1,193 @@
+use crate::{
+    cmd::test,
+    interpreter::{command::Command, interpreter::Interpreter, nodes::{Node, Sequence}},
+    shell::ShellState,
+    utils::{pattern, regex::{self, Regex}},
+};
+
+/// operators comparing two words, in addition to the ones of `test`
+const BINARY: &[&str] = &["=~"];
+
+impl<R: Fn() -> Option<String>, E: Fn(&mut ShellState, Vec<Command>) -> i32> Interpreter<R, E> {
+    /// Evaluates the conditional expression `[[ words ]]` and returns its status.
+    ///
+    /// Words are expanded without being split or globbed, and only when their value is needed.
+    /// The right side of `==` and `!=` is a pattern and the one of `=~` an extended regular expression,
+    /// their quoted parts matching literally. `=~` sets `BASH_REMATCH` to the text matched and the one
+    /// of each group.
+    pub(super) fn run_conditional(&self, state: &mut ShellState, words: Vec<Sequence>) -> i32 {
+        let mut conditional = Conditional {
+            inter: self,
+            state,
+            words: &words,
+            pos: 0,
+        };
+
+        let result = conditional.or(true).and_then(|result| match conditional.operator(0) {
+            Some(_) => Err(conditional.unexpected()),
+            None => Ok(result),
+        });
+        match result {
+            Ok(true) => 0,
+            Ok(false) => 1,
+            Err(e) => {
+                if !e.is_empty() {
+                    eprintln!("0-shell: {e}");
+                }
+                2
+            }
+        }
+    }
+}
+
+/// An expression being evaluated, with `||` binding looser than `&&`, and `&&` than `!`.
+///
+/// Each step is told whether its value is used: when it isn't, its words are read without being expanded.
+struct Conditional<'a, R: Fn() -> Option<String>, E: Fn(&mut ShellState, Vec<Command>) -> i32> {
+    inter: &'a Interpreter<R, E>,
+    state: &'a mut ShellState,
+    words: &'a [Sequence],
+    pos: usize,
+}
+
+impl<R: Fn() -> Option<String>, E: Fn(&mut ShellState, Vec<Command>) -> i32> Conditional<'_, R, E> {
+    /// The word `offset` words ahead when it's unquoted text, such as an operator, `Some("")` past
+    /// a quoted word and `None` at the end.
+    fn operator(&self, offset: usize) -> Option<String> {
+        let word = self.words.get(self.pos + offset)?;
+        let operator = match word.as_slice() {
+            [Node::Raw(raw)] => raw.clone(),
+            [Node::Operator(op)] => (*op).into(),
+            _ => String::new(),
+        };
+        Some(operator)
+    }
+
+    fn unexpected(&self) -> String {
+        match self.operator(0) {
+            Some(token) if !token.is_empty() => {
+                format!("syntax error in conditional expression: unexpected token `{token}'")
+            }
+            _ => "syntax error in conditional expression".into(),
+        }
+    }
+
+    /// Expands the next word, its quoted parts being escaped with `escape`.
+    fn word(&mut self, used: bool, escape: fn(&str) -> String) -> Result<String, String> {
+        let Some(word) = self.words.get(self.pos) else {
+            return Err("unexpected argument to conditional expression".into());
+        };
+        self.pos += 1;
+        if !used {
+            return Ok(String::new());
+        }
+
+        let mut text = String::new();
+        for node in word.clone() {
+            let quoted = matches!(node, Node::Quoted { .. });
+            let value = self.inter.node_to_string(self.state, node);
+            match quoted {
+                true => text.push_str(&escape(&value)),
+                false => text.push_str(&value),
+            }
+        }
+        Ok(text)
+    }
+
+    fn or(&mut self, used: bool) -> Result<bool, String> {
+        let mut result = self.and(used)?;
+        while self.operator(0).as_deref() == Some("||") {
+            self.pos += 1;
+            let right = self.and(used && !result)?;
+            result |= right;
+        }
+        Ok(result)
+    }
+
+    fn and(&mut self, used: bool) -> Result<bool, String> {
+        let mut result = self.not(used)?;
+        while self.operator(0).as_deref() == Some("&&") {
+            self.pos += 1;
+            let right = self.not(used && result)?;
+            result &= right;
+        }
+        Ok(result)
+    }
+
+    fn not(&mut self, used: bool) -> Result<bool, String> {
+        if self.operator(0).as_deref() == Some("!") {
+            self.pos += 1;
+            return self.not(used).map(|result| !result);
+        }
+        self.primary(used)
+    }
+
+    fn primary(&mut self, used: bool) -> Result<bool, String> {
+        let Some(first) = self.operator(0) else {
+            return Err(self.unexpected());
+        };
+
+        if first == "(" {
+            self.pos += 1;
+            let result = self.or(used)?;
+            if self.operator(0).as_deref() != Some(")") {
+                return Err(self.unexpected());
+            }
+            self.pos += 1;
+            return Ok(result);
+        }
+        if matches!(first.as_str(), ")" | "&&" | "||") {
+            return Err(self.unexpected());
+        }
+
+        if let Some(op) = self.operator(1)
+            && (test::BINARY.contains(&op.as_str()) || BINARY.contains(&op.as_str()))
+            && !matches!(op.as_str(), "-a" | "-o")
+        {
+            let left = self.word(used, str::to_owned)?;
+            self.pos += 1;
+            return match op.as_str() {
+                "=" | "==" | "!=" => {
+                    let pattern = self.word(used, pattern::escape)?;
+                    Ok(used && pattern::matches(&pattern, &left) == (op != "!="))
+                }
+                "=~" => {
+                    let expression = self.word(used, regex::escape)?;
+                    match used {
+                        true => self.matches(&left, &expression),
+                        false => Ok(false),
+                    }
+                }
+                _ => {
+                    let right = self.word(used, str::to_owned)?;
+                    match used {
+                        true => test::binary(&left, &op, &right).map_err(|e| format!("[[: {e}")),
+                        false => Ok(false),
+                    }
+                }
+            };
+        }
+
+        if test::UNARY.contains(&first.as_str()) && self.operator(1).is_some() {
+            self.pos += 1;
+            let operand = self.word(used, str::to_owned)?;
+            return match used {
+                true => test::unary(&first, &operand).map_err(|e| format!("[[: {e}")),
+                false => Ok(false),
+            };
+        }
+
+        let word = self.word(used, str::to_owned)?;
+        Ok(!word.is_empty())
+    }
+
+    /// Matches `text` against the regular expression `expression` and sets `BASH_REMATCH`,
+    /// an invalid expression is an error without a message.
+    fn matches(&mut self, text: &str, expression: &str) -> Result<bool, String> {
+        let regex = Regex::new(expression).map_err(|_| String::new())?;
+        let captures = regex.captures(text);
+        let matched = captures.is_some();
+        self.state.set_array("BASH_REMATCH", captures.unwrap_or_default());
+        Ok(matched)
+    }
+}
@@ -1,46 +1,285 @@
-
-use crate::interpreter::{command::Command, nodes::Node, parser::Parser, tokens::Operator};
+use crate::{
+    interpreter::{command::Command, nodes::{Node, Sequence, SubstitutionKind}, parser::Parser, tokens::Operator},
+    shell::{ShellState, history::with_history, options, signals},
+    utils::{pattern, process::wait_status, quote::quote},
+};
+use libc::{STDOUT_FILENO, dup2, fork};
 use std::{
+    cell::{Cell, RefCell},
     env,
-    io::{Read, pipe},
+    io::{Error, ErrorKind, Read, Write, pipe, stdout},
     iter::Peekable,
+    os::fd::AsRawFd,
+    process::exit,
 };
 
-pub struct Interpreter<R: Fn() -> String, E: Fn(Command) -> i32> {
+pub struct Interpreter<R: Fn() -> Option<String>, E: Fn(&mut ShellState, Vec<Command>) -> i32> {
     reader: R,
     executor: E,
+    /// set when an unset parameter was expanded with `nounset`
+    unbound: Cell<bool>,
 }
 
-impl<R: Fn() -> String, E: Fn(Command) -> i32> Interpreter<R, E> {
+impl<R: Fn() -> Option<String>, E: Fn(&mut ShellState, Vec<Command>) -> i32> Interpreter<R, E> {
     pub fn new(reader: R, executor: E) -> Self {
-        Self { reader, executor }
+        Self {
+            reader,
+            executor,
+            unbound: Cell::new(false),
+        }
+    }
+
+    pub fn exec(&self, state: &mut ShellState, pipeline: Vec<Command>) -> i32 {
+        (self.executor)(state, pipeline)
+    }
+
+    pub fn envar(&self, state: &ShellState, key: &str) -> String {
+        match key {
+            "?" => state.last_status.to_string(),
+            "!" => state.last_background.map(|pid| pid.to_string()).unwrap_or_default(),
+            "#" => state.positional.len().to_string(),
+            "@" | "*" => state.positional.join(" "),
+            "-" => state.flags(),
+            "0" => state.name.clone(),
+            _ if key.chars().all(|ch| ch.is_ascii_digit()) => {
+                let idx = key.parse::<usize>().unwrap_or(0);
+                state.positional.get(idx.wrapping_sub(1)).cloned().unwrap_or_default()
+            }
+            _ => state.var(key).unwrap_or_default(),
+        }
+    }
+
+    /// Parses `input` and executes each command as soon as it has been parsed,
+    /// so expansions of a command observe the effects of the previous ones.
+    ///
+    /// Returns the exit status of the last executed command.
+    pub fn parse_line(&self, state: &mut ShellState, input: &str) -> i32 {
+        let p = Parser::with_reader(input, &self.reader);
+        self.run_sequence(state, &mut p.peekable())
+    }
+
+    /// Parses and executes `source` line by line, as read from a file: each line is echoed with
+    /// `verbose` as it's read, and a construct left open at its end isn't completed with more input.
+    pub fn run_source(&self, state: &mut ShellState, source: &str) -> i32 {
+        let lines = RefCell::new(source.split_inclusive('\n'));
+        let read = || {
+            let line = lines.borrow_mut().next()?;
+            options::echo_verbose(line);
+            Some(line.to_string())
+        };
+        while let Some(line) = read() {
+            let p = Parser::with_reader(&line, &read);
+            self.run_sequence(state, &mut p.peekable());
+        }
+        state.last_status
+    }
+
+    /// Expands the parameters and command substitutions of `text` and removes its quotes,
+    /// white spaces and newlines are kept as they are.
+    pub fn expand(&self, state: &mut ShellState, text: &str) -> String {
+        let expanded = Parser::<fn() -> Option<String>>::new(text)
+            .map(|node| match node {
+                Node::Delimiter => "\n".into(),
+                Node::EOF => String::new(),
+                node => self.node_to_string(state, node),
+            })
+            .collect();
+        self.unbound.set(false);
+        expanded
+    }
+
+    /// Runs the body of the function `name` with `args` as positional parameters.
+    pub fn run_function(&self, state: &mut ShellState, name: &str, args: &[String]) -> i32 {
+        let Some(body) = state.functions.get(name).cloned() else {
+            eprintln!("0-shell: {name}: function not found");
+            return 127;
+        };
+
+        let positional = std::mem::replace(&mut state.positional, args.to_vec());
+        let exit_status = self.run_sequence(state, &mut body.into_iter().peekable());
+        state.positional = positional;
+        exit_status
+    }
+
+    /// Parses and executes commands until `seq` has been fully consumed.
+    ///
+    /// A pipeline following `&&` only runs when the previous one succeeded, and one following `||`
+    /// when it failed, otherwise it's skipped without being expanded.
+    fn run_sequence(&self, state: &mut ShellState, seq: &mut Peekable<impl Iterator<Item = Node>>) -> i32 {
+        // the `&&` or `||` ending the previous pipeline
+        let mut connector = None;
+
+        while seq.peek().is_some() {
+            if connector.is_some() {
+                // the next pipeline of the list may start on a following line
+                while let Some(Node::WhiteSpace(_) | Node::Delimiter) = seq.peek() {
+                    seq.next();
+                }
+            }
+
+            let run = match connector {
+                Some(Operator::AndIf) => state.last_status == 0,
+                Some(Operator::Or) => state.last_status != 0,
+                _ => true,
+            };
+            if !run {
+                connector = skip_pipeline(seq);
+                continue;
+            }
+
+            let pipeline = self.parse_pipeline(state, seq);
+            connector = pipeline
+                .last()
+                .and_then(|command| command.terminator)
+                .filter(|op| matches!(op, Operator::AndIf | Operator::Or));
+            self.run_pipeline(state, pipeline);
+
+            // the commands on the left of `&&` and `||` may fail
+            if state.options.errexit && state.last_status != 0 && connector.is_none() {
+                self.exit(state, state.last_status);
+            }
+        }
+
+        state.last_status
     }
 
-    pub fn exec(&self, command: Command) -> i32 {
-        (self.executor)(command)
+    /// Executes a pipeline once parsed, after its assignments, and sets `$?`.
+    fn run_pipeline(&self, state: &mut ShellState, mut pipeline: Vec<Command>) {
+        let mut failed = None;
+        for command in &pipeline {
+            if let Some(error) = &command.error {
+                eprint!("{error}");
+                // syntax errors have the status 2
+                let status = if error.kind() == ErrorKind::InvalidInput { 2 } else { 1 };
+                failed = failed.max(Some(status));
+            }
+        }
+        if let Some(status) = failed {
+            state.last_status = status;
+            // a non-interactive shell doesn't go on after an unset parameter
+            if self.unbound.take() && !state.interactive {
+                self.exit(state, 1);
+            }
+            return;
+        }
+
+        // a command made only of substitutions keeps the status they set
+        if pipeline.iter().all(|command| command.name.trim().is_empty() && command.assignments.is_empty()) {
+            return;
+        }
+
+        if state.options.xtrace {
+            self.trace(state, &pipeline);
+        }
+
+        let assignments: Vec<(String, String)> =
+            pipeline.iter_mut().flat_map(|command| std::mem::take(&mut command.assignments)).collect();
+        if let [command] = pipeline.as_slice()
+            && command.name.is_empty()
+        {
+            for (name, value) in assignments {
+                state.set_var(&name, Some(&value));
+            }
+            if !command.substituted {
+                state.last_status = 0;
+            }
+            return;
+        }
+
+        // assignments before a command are only seen by the pipeline
+        let saved: Vec<(String, Option<String>)> =
+            assignments.iter().map(|(name, _)| (name.clone(), env::var(name).ok())).collect();
+        for (name, value) in &assignments {
+            state.set_var(name, Some(value));
+        }
+
+        state.last_status = self.exec(state, pipeline);
+
+        for (name, value) in saved {
+            state.set_var(&name, value.as_deref());
+        }
+        self.run_traps(state);
+    }
+
+    /// Writes the expanded commands of `pipeline` to standard error, after the expansion of `PS4`.
+    fn trace(&self, state: &mut ShellState, pipeline: &[Command]) {
+        let ps4 = env::var("PS4").unwrap_or_else(|_| "+ ".into());
+        // the commands run to expand `PS4` aren't traced, and don't change `$?`
+        let last_status = state.last_status;
+        state.options.xtrace = false;
+        let prefix = self.expand(state, &ps4);
+        state.options.xtrace = true;
+        state.last_status = last_status;
+
+        for command in pipeline {
+            // only the value of an assignment is quoted
+            let assignments = command.assignments.iter().map(|(name, value)| format!("{name}={}", quote(value)));
+            let words = std::iter::once(&command.name).chain(&command.args).skip_while(|name| name.is_empty());
+            let words: Vec<String> = assignments.chain(words.map(|word| quote(word))).collect();
+            eprintln!("{prefix}{}", words.join(" "));
+        }
     }
 
-    pub fn envar(&self, key: &str) -> String {
-        env::var(key).unwrap_or_default()
+    /// Terminates the shell after a failure with `errexit` or `nounset`: the history is saved
+    /// and the `EXIT` trap runs.
+    fn exit(&self, state: &mut ShellState, status: i32) -> ! {
+        with_history(|history| history.finish());
+        self.run_exit_trap(state);
+        exit(status)
     }
 
-    pub fn parse_line(&self, input: &str) -> Vec<Command> {
-        let p = Parser::with_reader(&input, &self.reader);
-        let mut p = p.peekable();
-        let mut commands = Vec::new();
+    /// Runs the commands trapped on the signals received since the last call,
+    /// `$?` is preserved across them.
+    pub fn run_traps(&self, state: &mut ShellState) {
+        for sig in signals::take_pending() {
+            let Some(action) = state.traps.get(&sig).cloned() else {
+                continue;
+            };
+
+            let last_status = state.last_status;
+            self.parse_line(state, &action);
+            state.last_status = last_status;
+        }
+    }
 
-        while p.peek().is_some() {
-            commands.push(self.parse_sequence(&mut p));
+    /// Runs the command trapped on `EXIT`, at most once.
+    pub fn run_exit_trap(&self, state: &mut ShellState) {
+        if let Some(action) = state.traps.remove(&0) {
+            self.parse_line(state, &action);
         }
-        return commands;
+    }
+
+    /// Parses commands as long as they are connected with pipes.
+    fn parse_pipeline(&self, state: &mut ShellState, seq: &mut Peekable<impl Iterator<Item = Node>>) -> Vec<Command> {
+        let mut pipeline = Vec::new();
+
+        while seq.peek().is_some() {
+            let command = self.parse_sequence(state, seq);
+            let piped = command.terminator == Some(Operator::Pipe);
+            pipeline.push(command);
+            if !piped {
+                break;
+            }
+        }
+
+        pipeline
     }
 
     /// Parses a sequence until a delimiter occurs or `seq` has been fully consumed.
     ///
     /// Note: when a delimiter occurs the result is returned immediatly and the rest of `seq` is not necessary consumed.
-    pub fn parse_sequence(&self, seq: &mut Peekable<impl Iterator<Item = Node>>) -> Command {
+    pub fn parse_sequence(&self, state: &mut ShellState, seq: &mut Peekable<impl Iterator<Item = Node>>) -> Command {
         let mut command_sequence = Vec::new();
         let mut current = String::new();
+        // quoted empty strings are words too
+        let mut in_word = false;
+        // the current word with its quoted characters escaped, expanded to the paths it matches
+        let mut pattern = String::new();
+        // set while the current word is made of plain characters only, which may number a descriptor
+        let mut literal = true;
+        // whether the current word assigns a variable, decided once it's known whether it starts
+        // with a name followed by `=`, neither quoted nor expanded
+        let mut assignment = None;
 
         let mut command = Command::default();
         while let Some(node) = seq.next() {
@@ -49,42 +288,131 @@ impl<R: Fn() -> String, E: Fn(Command) -> i32> Interpreter<R, E> {
                 break;
             }
 
+            // the input ended inside a quote, a substitution or a function body
+            if let Some(closing) = unterminated(&node) {
+                let mut message = String::new();
+                if let Some(closing) = closing {
+                    message = format!("0-shell: unexpected EOF while looking for matching `{closing}'\n");
+                }
+                message.push_str("0-shell: syntax error: unexpected end of file\n");
+                command.error = Some(Error::new(ErrorKind::InvalidInput, message));
+                continue;
+            }
+
+            if let Node::Conditional(words) = node {
+                state.last_status = self.run_conditional(state, words);
+                continue;
+            }
+
+            if let Node::Function(body) = node {
+                if command_sequence.is_empty() && is_name(&current) {
+                    state.functions.insert(std::mem::take(&mut current), body);
+                    state.last_status = 0;
+                    in_word = false;
+                } else {
+                    command.error = Some(Error::new(ErrorKind::InvalidInput, "0-shell: syntax error near unexpected token `('\n"));
+                }
+                continue;
+            }
+
             if let Node::Operator(op) = node {
+                let Operator::Redirection(r) = op else {
+                    command.terminator = Some(op);
+                    break;
+                };
+
+                // digits right before the operator are the descriptor redirected
+                let mut fd = None;
+                if in_word && literal && !current.is_empty() && current.bytes().all(|b| b.is_ascii_digit()) {
+                    fd = current.parse().ok();
+                    if fd.is_none() {
+                        command.error = Some(Error::other(format!("0-shell: {current}: Bad file descriptor\n")));
+                    }
+                    current.clear();
+                    pattern.clear();
+                    in_word = false;
+                }
+
                 //consume white spaces
                 while let Some(Node::WhiteSpace(_)) = seq.peek() {
                     seq.next();
                 }
 
-                if let Some(_) = seq.peek() {
-                    if let Operator::Redirection(r) = op {
-                        command.handle_redirection(r, self.node_to_string(seq.next().unwrap()));
-                    } else {
-                        break;
-                    }
+                if let Some(operand) = seq.next()
+                    && command.error.is_none()
+                {
+                    let operand = self.node_to_string(state, operand);
+                    command.handle_redirection(r, fd, operand, state.options.noclobber);
                 }
                 //TODO: return parse error
                 continue;
             }
 
+            // `"$@"` makes a word of each positional parameter
+            // the value of an assignment is a single word
+            let assigning = assignment == Some(true) && command_sequence.is_empty();
+            let node = match node {
+                Node::Quoted { value, .. } if value.iter().any(is_all_positional) && !assigning => {
+                    command.substituted |= value.iter().any(has_substitution);
+                    literal = false;
+                    assignment.get_or_insert(false);
+                    for (i, field) in self.quoted_fields(state, value).into_iter().enumerate() {
+                        if i > 0 {
+                            let word = std::mem::take(&mut current);
+                            push_word(state, &mut command_sequence, word, std::mem::take(&mut pattern));
+                        }
+                        pattern.push_str(&pattern::escape(&field));
+                        current.push_str(&field);
+                        in_word = true;
+                    }
+                    continue;
+                }
+                node => node,
+            };
+
             // push non-whitespace characters
             if !matches!(node, Node::WhiteSpace(_)) {
-                current.push_str(&self.node_to_string(node));
+                // unquoted expansions that are empty don't make a word
+                let expansion = matches!(node, Node::ParameterExpansion(_) | Node::Substitution { .. });
+                command.substituted |= has_substitution(&node);
+                let quoted = matches!(node, Node::Quoted { .. });
+                literal &= matches!(node, Node::Raw(_));
+                if assignment.is_none() {
+                    assignment = match &node {
+                        Node::Raw(text) => text.find('=').map(|end| is_name(&format!("{current}{}", &text[..end]))),
+                        _ => Some(false),
+                    };
+                }
+                let value = self.node_to_string(state, node);
+                in_word |= !(expansion && value.is_empty());
+                current.push_str(&value);
+                match quoted {
+                    true => pattern.push_str(&pattern::escape(&value)),
+                    false => pattern.push_str(&value),
+                }
                 continue;
             }
 
             // skip leading white spaces
-            if current.is_empty() {
+            if !in_word {
                 continue;
             }
 
             // separate arguments by white spaces
-            command_sequence.push(current.to_string());
-            current.clear();
+            let word = std::mem::take(&mut current);
+            end_word(state, &mut command, &mut command_sequence, word, std::mem::take(&mut pattern), assignment);
+            in_word = false;
+            literal = true;
+            assignment = None;
         }
 
         // push last argument
-        if !current.is_empty() {
-            command_sequence.push(current.to_string());
+        if in_word {
+            end_word(state, &mut command, &mut command_sequence, current, pattern, assignment);
+        }
+
+        if self.unbound.get() && command.error.is_none() {
+            command.error = Some(Error::other(""));
         }
 
         if command_sequence.len() > 0 {
@@ -98,51 +426,197 @@ impl<R: Fn() -> String, E: Fn(Command) -> i32> Interpreter<R, E> {
         return command;
     }
 
-    /// Parses substitution sequences, executes each command, and joins results with spaces.
+    /// Executes the substitution sequence in a subshell and returns its output,
+    /// `$?` is set to the exit status of the subshell.
     /// https://pubs.opengroup.org/onlinepubs/9699919799/utilities/V3_chap02.html#tag_18_06_03
-    pub fn parse_substitution(&self, seq: impl Iterator<Item = Node>) -> String {
-        let mut iter = seq.peekable();
-        let mut result = Vec::new();
-
-        while let Some(_) = iter.peek() {
-            let mut command = self.parse_sequence(&mut iter);
-            let p = pipe();
-            if p.is_err() {
-                let exit_status = self.exec(command);
-                continue;
+    pub fn parse_substitution(&self, state: &mut ShellState, seq: impl Iterator<Item = Node>) -> String {
+        let (mut reader, writer) = match pipe() {
+            Ok(p) => p,
+            Err(e) => {
+                eprintln!("0-shell: pipe failed: {e}");
+                state.last_status = 1;
+                return String::new();
             }
+        };
 
-            // unwrap pipe on success, and redirect command output to it
-            let (mut r, w) = p.unwrap();
-            command.io_streams.stdout.push(Box::new(w));
-            let exit_status = self.exec(command);
+        let pid = unsafe { fork() };
+        if pid == -1 {
+            eprintln!("0-shell: fork failed: {}", std::io::Error::last_os_error());
+            state.last_status = 1;
+            return String::new();
+        }
+
+        // child: run the commands with stdout redirected to the pipe
+        if pid == 0 {
+            state.subshell();
+            drop(reader);
+            if unsafe { dup2(writer.as_raw_fd(), STDOUT_FILENO) } == -1 {
+                eprintln!("dup2 failed: {}", std::io::Error::last_os_error());
+                exit(1);
+            }
+            drop(writer);
 
-            // read redirected output
-            let mut line = String::new();
-            let _ = r.read_to_string(&mut line);
-            result.push(line.trim_end().to_string());
+            let exit_status = self.run_sequence(state, &mut seq.peekable());
+            let _ = stdout().flush();
+            exit(exit_status);
         }
 
-        return result.join(" ");
+        // the output has to be read while the child is running,
+        // otherwise it blocks forever once the pipe buffer is full
+        drop(writer);
+        let mut output = Vec::new();
+        let _ = reader.read_to_end(&mut output);
+        state.last_status = wait_status(pid);
+
+        // only trailing newlines are removed, other trailing white spaces are part of the result
+        let mut output = String::from_utf8_lossy(&output).into_owned();
+        output.truncate(output.trim_end_matches('\n').len());
+        output
     }
 
-    fn node_to_string(&self, node: Node) -> String {
+    /// Expands the content of double quotes holding `$@`: the first positional parameter ends
+    /// the field started before it and the last one starts the field going on after it.
+    /// No field is left when there are no positional parameters and nothing else in the quotes.
+    fn quoted_fields(&self, state: &mut ShellState, value: Sequence) -> Vec<String> {
+        let mut fields = vec![String::new()];
+        let mut expanded = false;
+        for node in value {
+            if is_all_positional(&node) {
+                for (i, arg) in state.positional.iter().enumerate() {
+                    if i > 0 {
+                        fields.push(String::new());
+                    }
+                    fields.last_mut().unwrap().push_str(arg);
+                    expanded = true;
+                }
+                continue;
+            }
+            let value = self.node_to_string(state, node);
+            expanded |= !value.is_empty();
+            fields.last_mut().unwrap().push_str(&value);
+        }
+
+        if !expanded {
+            fields.clear();
+        }
+        fields
+    }
+
+    pub(super) fn node_to_string(&self, state: &mut ShellState, node: Node) -> String {
         match node {
             Node::Raw(str) => str,
             Node::Quoted { value, .. } => {
                 let mut res = String::new();
 
                 for node in value {
-                    res.push_str(&self.node_to_string(node));
+                    res.push_str(&self.node_to_string(state, node));
                 }
                 return res;
             }
-            Node::ParameterExpansion(param) => self.envar(&param),
-            Node::Substitution { value, .. } => self.parse_substitution(value.into_iter()),
+            Node::ParameterExpansion(param) if state.options.nounset && is_unset(state, &param) => {
+                eprintln!("0-shell: {param}: unbound variable");
+                self.unbound.set(true);
+                String::new()
+            }
+            Node::ParameterExpansion(param) => self.envar(state, &param),
+            Node::Substitution { value, .. } => self.parse_substitution(state, value.into_iter()),
             Node::WhiteSpace(ch) => ch.into(),
             Node::Operator(op) => op.into(),
-            Node::Delimiter => "".into(),
+            Node::Function(_) | Node::Conditional(_) | Node::Delimiter => "".into(),
             Node::EOF => "\0".into(),
         }
     }
 }
+
+/// Ends a word of `command`: an assignment before its name is kept apart, unexpanded to paths,
+/// other words are added to `words`.
+fn end_word(
+    state: &ShellState,
+    command: &mut Command,
+    words: &mut Vec<String>,
+    word: String,
+    pattern: String,
+    assignment: Option<bool>,
+) {
+    if assignment == Some(true)
+        && words.is_empty()
+        && let Some((name, value)) = word.split_once('=')
+    {
+        command.assignments.push((name.to_string(), value.to_string()));
+        return;
+    }
+    push_word(state, words, word, pattern);
+}
+
+/// Adds `word` to the words of a command, or the paths matched by `pattern` when there are some.
+fn push_word(state: &ShellState, words: &mut Vec<String>, word: String, pattern: String) {
+    if !state.options.noglob && pattern::has_wildcards(&pattern) {
+        let paths = pattern::glob(&pattern);
+        if !paths.is_empty() {
+            words.extend(paths);
+            return;
+        }
+    }
+    words.push(word);
+}
+
+/// Consumes the nodes of a pipeline without expanding them, returns the `&&` or `||` ending it.
+fn skip_pipeline(seq: &mut impl Iterator<Item = Node>) -> Option<Operator> {
+    for node in seq {
+        match node {
+            Node::Operator(op @ (Operator::AndIf | Operator::Or)) => return Some(op),
+            Node::Operator(Operator::SemiColon | Operator::And) | Node::Delimiter => return None,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// whether the parameter `name` is unset, special parameters always being set
+fn is_unset(state: &ShellState, name: &str) -> bool {
+    match name {
+        "?" | "#" | "@" | "*" | "-" | "0" => false,
+        "!" => state.last_background.is_none(),
+        _ if name.chars().all(|ch| ch.is_ascii_digit()) => {
+            name.parse::<usize>().map_or(true, |idx| idx > state.positional.len())
+        }
+        _ => state.var(name).is_none(),
+    }
+}
+
+/// whether `word` can name a variable or a function
+pub fn is_name(word: &str) -> bool {
+    let mut chars = word.chars();
+    chars.next().is_some_and(|ch| ch.is_ascii_alphabetic() || ch == '_')
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+}
+
+fn is_all_positional(node: &Node) -> bool {
+    matches!(node, Node::ParameterExpansion(param) if param == "@")
+}
+
+fn has_substitution(node: &Node) -> bool {
+    match node {
+        Node::Substitution { .. } => true,
+        Node::Quoted { value, .. } => value.iter().any(has_substitution),
+        _ => false,
+    }
+}
+
+/// The closing character missing from `node` when the input ended before it,
+/// `Some(None)` when the input ended without one being expected.
+fn unterminated(node: &Node) -> Option<Option<char>> {
+    let (closing, value) = match node {
+        Node::EOF => return Some(None),
+        Node::Quoted { kind, value } => (kind.clone().into(), value),
+        Node::Substitution { kind, value } => match kind {
+            SubstitutionKind::RoundBracket => (')', value),
+            SubstitutionKind::BackQuote => ('`', value),
+        },
+        _ => return None,
+    };
+    // the innermost construct left open is reported
+    let inner = value.iter().find_map(unterminated)?;
+    Some(inner.or(Some(closing)))
+}
+
@@ -1,4 +1,5 @@
 pub mod command;
+pub mod conditional;
 pub mod interpreter;
 pub mod nodes;
 pub mod parser;
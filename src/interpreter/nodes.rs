@@ -1,13 +1,13 @@
 use super::tokens::*;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum SubstitutionKind {
     RoundBracket,
     BackQuote,
 }
 pub type Sequence = Vec<Node>;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum Quote {
     Single,
     Double,
@@ -21,23 +21,24 @@ impl Into<char> for Quote {
         }
     }
 }
-#[derive(Debug)]
-/// TODO: use ignored `Quoted.kind` and `Substitution.kind`
+#[derive(Clone, Debug)]
 pub enum Node {
     Raw(String),
     Quoted {
-        #[allow(dead_code)]
         kind: Quote,
         value: Sequence,
     },
     ParameterExpansion(String),
     Substitution {
-        #[allow(dead_code)]
         kind: SubstitutionKind,
         value: Sequence,
     },
     WhiteSpace(char),
     Operator(Operator),
+    /// the body of a function definition `name() { ... }`, the name being the word before it
+    Function(Sequence),
+    /// the words of a conditional expression `[[ ... ]]`, operators being words of their own
+    Conditional(Vec<Sequence>),
     Delimiter,
     EOF,
 }
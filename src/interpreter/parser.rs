@@ -1,24 +1,43 @@
 use std::env;
 
-use crate::interpreter::{
-    nodes::{self, Node, Sequence, SubstitutionKind},
-    tokenizer::Tokenizer,
-    tokens::{self, Quote, Token},
+use crate::{
+    interpreter::{
+        nodes::{self, Node, Sequence, SubstitutionKind},
+        tokenizer::Tokenizer,
+        tokens::{self, Quote, Token},
+    },
+    shell::aliases,
 };
 
+/// words with a meaning of their own where a command starts
+pub const KEYWORDS: &[&str] = &["[[", "]]", "{", "}"];
+
 /// Parses shell input according to [`POSIX Shell Command Language`](https://pubs.opengroup.org/onlinepubs/9699919799/utilities/V3_chap02.html)
-pub struct Parser<T: Fn() -> String> {
+pub struct Parser<T: Fn() -> Option<String>> {
     tokenizer: Tokenizer,
     context: Option<Token>,
     reader: Option<T>,
+    /// the next token starts a word
+    word_start: bool,
+    /// the next word is the name of a command, and may be an alias
+    command_start: bool,
+    /// the next word follows an alias ending with a blank, and may be an alias too
+    alias_next: bool,
+    /// aliases being expanded: their name, the position where their text ends,
+    /// and whether it ends with a blank
+    expanding: Vec<(String, usize, bool)>,
 }
 
-impl<T: Fn() -> String> Parser<T> {
-    pub fn _new(source: &str) -> Parser<T> {
+impl<T: Fn() -> Option<String>> Parser<T> {
+    pub fn new(source: &str) -> Parser<T> {
         Self {
             tokenizer: Tokenizer::new(source),
             context: None,
             reader: None,
+            word_start: true,
+            command_start: true,
+            alias_next: false,
+            expanding: Vec::new(),
         }
     }
 
@@ -27,17 +46,20 @@ impl<T: Fn() -> String> Parser<T> {
             tokenizer: Tokenizer::new(source),
             context: None,
             reader: Some(reader),
+            word_start: true,
+            command_start: true,
+            alias_next: false,
+            expanding: Vec::new(),
         }
     }
 
+    /// Reads more input for a construct left open, returns `false` at the end of input.
     pub fn feed(&mut self) -> bool {
-        match &self.reader {
-            None => false,
-            Some(read) => {
-                self.tokenizer.feed(&read());
-                true
-            }
-        }
+        let Some(line) = self.reader.as_ref().and_then(|read| read()) else {
+            return false;
+        };
+        self.tokenizer.feed(&line);
+        true
     }
 
     fn get_raw<P: Into<String>>(&mut self, first: P) -> String {
@@ -79,25 +101,74 @@ impl<T: Fn() -> String> Parser<T> {
         match quote {
             // single quote has no meaning inside double quotes
             Quote::Single if inside_double => Node::Raw(self.get_raw('\'')),
+            Quote::Back => Node::Substitution {
+                kind: SubstitutionKind::BackQuote,
+                value: self.get_backquoted(inside_double),
+            },
             q => {
                 self.context = Some(Token::Quote(q));
                 let seq = self.get_sequence(|t| matches!(t, Token::Quote(q2) if quote == q2));
-                match q {
-                    Quote::Double => Node::Quoted {
-                        kind: nodes::Quote::Double,
-                        value: seq,
-                    },
-                    Quote::Back => Node::Substitution {
-                        kind: SubstitutionKind::BackQuote,
-                        value: seq,
-                    },
-                    Quote::Single => Node::Quoted {
-                        kind: nodes::Quote::Single,
-                        value: seq,
-                    },
+                let kind = match q {
+                    Quote::Double => nodes::Quote::Double,
+                    _ => nodes::Quote::Single,
+                };
+                Node::Quoted { kind, value: seq }
+            }
+        }
+    }
+
+    /// Collects the text of a backquoted command substitution and parses it as a separate command.
+    ///
+    /// Inside backquotes a backslash keeps its literal meaning except when followed by
+    /// '$', '`', '\\' (or '"' when the substitution is inside double-quotes),
+    /// which allows nesting with escaped backquotes.
+    /// https://pubs.opengroup.org/onlinepubs/9699919799/utilities/V3_chap02.html#tag_18_06_03
+    fn get_backquoted(&mut self, inside_double: bool) -> Sequence {
+        let mut source = String::new();
+        let mut escaped = false;
+
+        loop {
+            let Some(token) = self.tokenizer.next() else {
+                if self.feed() {
+                    continue;
                 }
+                return vec![Node::EOF];
+            };
+
+            match token {
+                Token::EOF => return vec![Node::EOF],
+                Token::Quote(Quote::Back) if !escaped => break,
+                Token::BackSlash if !escaped => {
+                    escaped = true;
+                    continue;
+                }
+                Token::DollarSign | Token::Quote(Quote::Back) | Token::BackSlash => {}
+                Token::Quote(Quote::Double) if inside_double => {}
+                _ if escaped => source.push('\\'),
+                _ => {}
             }
+            escaped = false;
+            source.push_str(&String::from(token));
+        }
+
+        let mut parser = Parser::<T>::new(&source);
+        parser.context = Some(Token::Quote(Quote::Back));
+        parser.collect()
+    }
+
+    /// collects the characters of a parameter name
+    fn get_name(&mut self, first: char) -> String {
+        let mut name = String::from(first);
+
+        while let Token::RawChar(ch) = self.tokenizer.current {
+            if !(ch.is_ascii_alphanumeric() || ch == '_') {
+                break;
+            }
+            self.tokenizer.next();
+            name.push(ch);
         }
+
+        name
     }
 
     fn handle_dollar_sign(&mut self) -> Node {
@@ -113,15 +184,150 @@ impl<T: Fn() -> String> Parser<T> {
             };
         }
 
+        // `${name}`, the name may be followed by an index in an array
+        if let Token::Bracket('{') = self.tokenizer.current {
+            self.tokenizer.next(); // consume opening brace
+            let mut name = String::new();
+            loop {
+                match self.tokenizer.current {
+                    Token::Bracket('}') => break,
+                    Token::WhiteSpace('\n') | Token::EOF => return Node::Raw(format!("${{{name}")),
+                    token => {
+                        self.tokenizer.next();
+                        name.push_str(&String::from(token));
+                    }
+                }
+            }
+            self.tokenizer.next(); // consume closing brace
+            return Node::ParameterExpansion(name);
+        }
+
         // handle paramter expansion
         if let Token::RawChar(ch) = self.tokenizer.current {
-            self.tokenizer.next(); //consume the raw character after $
-            return Node::ParameterExpansion(self.get_raw(ch));
+            // special and positional parameters are a single character
+            if matches!(ch, '?' | '!' | '#' | '@' | '*' | '-' | '0'..='9') {
+                self.tokenizer.next();
+                return Node::ParameterExpansion(ch.into());
+            }
+
+            if ch.is_ascii_alphabetic() || ch == '_' {
+                self.tokenizer.next(); //consume the raw character after $
+                return Node::ParameterExpansion(self.get_name(ch));
+            }
         }
 
         Node::Raw("$".into())
     }
 
+    /// Collects the body of a function definition, the commands between `{` and the matching `}`,
+    /// reading more lines when the body isn't complete yet.
+    fn get_function_body(&mut self) -> Node {
+        // the opening brace may come on a following line
+        loop {
+            match self.tokenizer.current {
+                Token::WhiteSpace(_) => {
+                    if self.tokenizer.next().is_none() && !self.feed() {
+                        return Node::EOF;
+                    }
+                }
+                Token::Bracket('{') => {
+                    self.tokenizer.next();
+                    self.command_start = true;
+                    break;
+                }
+                _ => return Node::Raw("()".into()),
+            }
+        }
+
+        let mut body = Sequence::new();
+        let mut depth = 1;
+        // braces are only reserved words at the start of a command word
+        let mut word_start = true;
+        loop {
+            let Some(node) = self.next() else {
+                if self.feed() {
+                    continue;
+                }
+                return Node::EOF;
+            };
+
+            match &node {
+                Node::Raw(word) if word_start && word == "{" && matches!(self.tokenizer.current, Token::WhiteSpace(_)) => {
+                    depth += 1;
+                    self.command_start = true;
+                }
+                Node::Raw(word) if word_start && word == "}" => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Node::EOF => return Node::EOF,
+                _ => {}
+            }
+
+            word_start = matches!(node, Node::WhiteSpace(_) | Node::Delimiter | Node::Operator(_));
+            body.push(node);
+        }
+
+        Node::Function(body)
+    }
+
+    /// Collects the words of a conditional expression, up to the `]]` closing it.
+    /// Operators and parentheses are words of their own, except in the regular expression after `=~`.
+    fn get_conditional(&mut self) -> Node {
+        let mut words = Vec::new();
+        let mut word = Sequence::new();
+        let mut regex = false;
+        loop {
+            // the `~` of `=~` isn't a home directory
+            if matches!(word.as_slice(), [Node::Raw(op)] if op == "=") && self.tokenizer.current == Token::Tilde {
+                self.tokenizer.next();
+                word = vec![Node::Raw("=~".into())];
+                continue;
+            }
+
+            let Some(node) = self.next() else {
+                if self.feed() {
+                    continue;
+                }
+                return Node::EOF;
+            };
+            // words following operators aren't aliases
+            self.command_start = false;
+
+            match node {
+                Node::EOF => return Node::EOF,
+                Node::Raw(raw)
+                    if raw == "]]"
+                        && word.is_empty()
+                        && matches!(self.tokenizer.current, Token::WhiteSpace(_) | Token::Operator(_) | Token::EOF) =>
+                {
+                    break;
+                }
+                Node::WhiteSpace(_) | Node::Delimiter => {
+                    if !word.is_empty() {
+                        regex = matches!(word.as_slice(), [Node::Raw(op)] if op == "=~");
+                        words.push(std::mem::take(&mut word));
+                    }
+                }
+                Node::Operator(op) if regex => word.push(Node::Raw(op.into())),
+                Node::Raw(raw) if !regex && (raw == "(" || raw == ")") => {
+                    words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+                    words.push(vec![Node::Raw(raw)]);
+                }
+                node @ Node::Operator(_) => {
+                    words.extend((!word.is_empty()).then(|| std::mem::take(&mut word)));
+                    words.push(vec![node]);
+                    regex = false;
+                }
+                node => word.push(node),
+            }
+        }
+
+        Node::Conditional(words)
+    }
+
     fn escape_next(&mut self) -> Node {
         use Token::*;
         use tokens::Quote::*;
@@ -139,6 +345,9 @@ impl<T: Fn() -> String> Parser<T> {
 
             // https://pubs.opengroup.org/onlinepubs/9699919799/utilities/V3_chap02.html#tag_18_02_01
             if let WhiteSpace('\n') = token {
+                if self.tokenizer.current == EOF && !self.feed() {
+                    return Node::EOF;
+                }
                 return self.next().unwrap_or(Node::EOF);
             }
 
@@ -149,17 +358,72 @@ impl<T: Fn() -> String> Parser<T> {
                     _ => Node::Raw(String::from("\\") + &String::from(token)),
                 },
 
-                _ => Node::Raw(token.into()),
+                // an escaped character is quoted, which keeps it from being a pattern
+                _ => Node::Quoted {
+                    kind: nodes::Quote::Single,
+                    value: vec![Node::Raw(token.into())],
+                },
             };
         }
     }
+    /// Replaces `word`, just read, with the alias it names when it's the name of a command.
+    /// An alias isn't expanded again within its own text, which ends recursive aliases.
+    ///
+    /// Returns whether `word` has been replaced.
+    fn expand_alias(&mut self, word: &str) -> bool {
+        // only unquoted words are aliases
+        let whole_word = matches!(self.tokenizer.current, Token::WhiteSpace(_) | Token::Operator(_) | Token::EOF);
+        if self.context.is_some()
+            || !self.word_start
+            || !(self.command_start || self.alias_next)
+            || !whole_word
+            || self.expanding.iter().any(|(name, _, _)| name == word)
+        {
+            return false;
+        }
+        let Some(value) = aliases::get(word) else {
+            return false;
+        };
+
+        // the text is inserted before the end of the aliases being expanded
+        let pos = self.tokenizer.pos;
+        let len = value.chars().count();
+        for (_, end, _) in &mut self.expanding {
+            if *end >= pos {
+                *end += len;
+            }
+        }
+        let blank = value.ends_with([' ', '\t']);
+        self.expanding.push((word.to_string(), pos + len, blank));
+        self.tokenizer.prepend(&value);
+        true
+    }
+
+    /// Skips the blanks and newlines after an operator that needs a command to follow,
+    /// reading more input when the line ends.
+    fn skip_linebreak(&mut self) {
+        loop {
+            match self.tokenizer.current {
+                Token::WhiteSpace(_) => {
+                    self.tokenizer.next();
+                }
+                Token::EOF => {
+                    if !self.feed() {
+                        break;
+                    }
+                }
+                _ => break,
+            }
+        }
+    }
+
     fn handle_white_space(&self, w: char) -> Node {
         use Token::*;
         use tokens::Quote::*;
 
         let inside_substitution = matches!(self.context, Some(DollarSign | Quote(Back)));
-        // escaped newline in substitution is a line continuation
-        if inside_substitution && w == '\n' {
+        // an unquoted newline ends the command, an escaped one is a line continuation
+        if (inside_substitution || self.context.is_none()) && w == '\n' {
             Node::Delimiter
         } else {
             Node::WhiteSpace(w)
@@ -167,10 +431,17 @@ impl<T: Fn() -> String> Parser<T> {
     }
 }
 
-impl<T: Fn() -> String> Iterator for Parser<T> {
+impl<T: Fn() -> Option<String>> Iterator for Parser<T> {
     type Item = Node;
 
     fn next(&mut self) -> Option<Self::Item> {
+        // the text of the aliases read entirely
+        let pos = self.tokenizer.pos;
+        for (_, _, blank) in self.expanding.iter().filter(|(_, end, _)| *end <= pos) {
+            self.alias_next |= *blank;
+        }
+        self.expanding.retain(|(_, end, _)| *end > pos);
+
         let current = self.tokenizer.next()?;
 
         // anything inside single quote is literal and has no meaning, it should be handled as raw
@@ -178,6 +449,17 @@ impl<T: Fn() -> String> Iterator for Parser<T> {
             return Some(Node::Raw(self.get_raw(current)));
         }
 
+        // a `#` starting a word comments out the rest of the line
+        let inside_substitution = matches!(self.context, Some(Token::DollarSign | Token::Quote(Quote::Back)));
+        if current == Token::RawChar('#') && self.word_start && (self.context.is_none() || inside_substitution) {
+            while !matches!(self.tokenizer.current, Token::WhiteSpace('\n') | Token::EOF) {
+                if self.tokenizer.next().is_none() {
+                    break;
+                }
+            }
+            return self.next();
+        }
+
         let parent = self.context;
         let node = match current {
             Token::Tilde => {
@@ -187,17 +469,48 @@ impl<T: Fn() -> String> Iterator for Parser<T> {
                     Node::Raw(env::var("HOME").unwrap_or_default())
                 }
             }
-            Token::RawChar(ch) => Node::Raw(self.get_raw(ch)),
+            Token::RawChar(ch) => {
+                let word = self.get_raw(ch);
+                if self.expand_alias(&word) {
+                    return self.next();
+                }
+                let whole_word = matches!(self.tokenizer.current, Token::WhiteSpace(_) | Token::Operator(_) | Token::EOF);
+                match word.as_str() {
+                    "[[" if self.context.is_none() && self.word_start && self.command_start && whole_word => {
+                        self.get_conditional()
+                    }
+                    _ => Node::Raw(word),
+                }
+            }
             Token::Quote(q) => self.get_quoted(q),
             Token::DollarSign => self.handle_dollar_sign(),
             Token::BackSlash => self.escape_next(),
-            Token::Operator(op) => Node::Operator(op),
+            Token::Operator(op) => {
+                if self.context.is_none() && matches!(op, tokens::Operator::Pipe | tokens::Operator::AndIf | tokens::Operator::Or) {
+                    self.skip_linebreak();
+                }
+                Node::Operator(op)
+            }
             Token::WhiteSpace(w) => self.handle_white_space(w),
+            // `name()` starts a function definition
+            Token::Bracket('(') if self.context.is_none() && self.tokenizer.current == Token::Bracket(')') => {
+                self.tokenizer.next();
+                self.get_function_body()
+            }
             Token::Bracket(ch) => Node::Raw(ch.into()),
             Token::EOF => Node::EOF,
         };
 
         self.context = parent; // in case the context has changed inside get_quote
+        self.word_start = matches!(current, Token::WhiteSpace(_) | Token::Operator(_));
+        if !matches!(node, Node::WhiteSpace(_)) {
+            self.alias_next = false;
+            self.command_start = match &node {
+                Node::Delimiter => true,
+                Node::Operator(op) => !matches!(op, tokens::Operator::Redirection(_)),
+                _ => false,
+            };
+        }
         Some(node)
     }
 }
@@ -1,5 +1,6 @@
 use super::tokens::{
     Operator::*,
+    RedirectionKind,
     Token::{self, *},
 };
 
@@ -8,6 +9,8 @@ use std::{iter::Peekable, vec::IntoIter};
 pub struct Tokenizer {
     pub chars: Peekable<IntoIter<char>>,
     pub current: Token,
+    /// position of the current token in the input read so far
+    pub pos: usize,
 }
 
 impl Tokenizer {
@@ -15,12 +18,28 @@ impl Tokenizer {
         let mut chars = source.chars().collect::<Vec<char>>().into_iter().peekable();
         let current = chars.next().map_or(EOF, Into::into);
 
-        Self { chars, current }
+        Self { chars, current, pos: 0 }
     }
 
-    /// replaces the current source with a new one
+    /// replaces the current source with a new one, the source read so far having been yielded entirely
     pub fn feed(&mut self, source: &str) {
         self.chars = source.chars().collect::<Vec<char>>().into_iter().peekable();
+        if self.current == EOF {
+            self.current = self.chars.next().map_or(EOF, Into::into);
+        }
+    }
+
+    /// inserts `text` before the current token, its first character becoming the current one
+    pub fn prepend(&mut self, text: &str) {
+        let mut chars: Vec<char> = text.chars().collect();
+        if self.current != EOF {
+            chars.extend(String::from(self.current).chars());
+        }
+        chars.extend(&mut self.chars);
+
+        let mut chars = chars.into_iter().peekable();
+        self.current = chars.next().map_or(EOF, Into::into);
+        self.chars = chars;
     }
 }
 
@@ -28,19 +47,30 @@ impl Iterator for Tokenizer {
     type Item = Token;
 
     fn next(&mut self) -> Option<Token> {
+        if self.current == EOF {
+            return None;
+        }
         let token = match (self.current, self.chars.peek()) {
             (Operator(And), Some('&')) => {
                 self.chars.next();
+                self.pos += 1;
                 Operator(AndIf)
             }
             (Operator(Pipe), Some('|')) => {
                 self.chars.next();
+                self.pos += 1;
                 Operator(Or)
             }
+            (Operator(Redirection(RedirectionKind::Output)), Some('|')) => {
+                self.chars.next();
+                self.pos += 1;
+                Operator(Redirection(RedirectionKind::Clobber))
+            }
             (token, _) => token,
         };
 
-        self.current = self.chars.next()?.into();
+        self.current = self.chars.next().map_or(EOF, Into::into);
+        self.pos += 1;
         Some(token)
     }
 }
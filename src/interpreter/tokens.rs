@@ -9,6 +9,8 @@ pub enum Quote {
 pub enum RedirectionKind {
     Input,
     Output,
+    /// `>|`, overwriting a file even with `noclobber`
+    Clobber,
     Error,
     OutputError,
 }
@@ -19,6 +21,7 @@ impl From<RedirectionKind> for &str {
         match x {
             Input => "<",
             Output => ">",
+            Clobber => ">|",
             Error => "2>",
             OutputError => "&>",
         }
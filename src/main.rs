@@ -2,15 +2,10 @@ use crate::cli::read_input;
 mod cli;
 mod cmd;
 mod interpreter;
+mod shell;
 mod utils;
 
 
 fn main() {
-    
-    // welcome func
-    if let Err(err) = cli::print("$ ") {
-        panic!("{}", err);
-    }
-
     read_input()
 }
@@ -0,0 +1,20 @@
+use std::{cell::RefCell, collections::BTreeMap};
+
+thread_local! {
+    static ALIASES: RefCell<BTreeMap<String, String>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+/// runs `f` with the aliases defined by `alias`, kept apart from the state as the parser expands them
+pub fn with_aliases<T>(f: impl FnOnce(&mut BTreeMap<String, String>) -> T) -> T {
+    ALIASES.with_borrow_mut(f)
+}
+
+/// the value of the alias `name`
+pub fn get(name: &str) -> Option<String> {
+    with_aliases(|aliases| aliases.get(name).cloned())
+}
+
+/// whether `name` can be defined as an alias
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && !name.chars().any(|ch| ch.is_whitespace() || "/$`=|&;()<>'\"\\".contains(ch))
+}
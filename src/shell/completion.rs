@@ -0,0 +1,176 @@
+use std::{collections::BTreeSet, env, fs, os::unix::fs::PermissionsExt, path::PathBuf};
+
+use crate::cmd::builtin;
+
+/// How the arguments of a command are completed, as set by `complete`.
+#[derive(Clone, Default)]
+pub struct CompSpec {
+    /// `-W`: words split on white spaces
+    pub words: Option<String>,
+    /// `-F`: function setting `COMPREPLY` to the candidates, one per line
+    pub function: Option<String>,
+    /// `-d`: directory names
+    pub dirs: bool,
+    /// `-f`: file names
+    pub files: bool,
+    /// `-c`: command names
+    pub commands: bool,
+}
+
+/// The command line being completed.
+pub struct CompletionRequest<'a> {
+    pub line: &'a str,
+    /// position of the cursor in `line`, in bytes
+    pub point: usize,
+    /// words of the command under the cursor, as typed
+    pub words: Vec<String>,
+    /// index in `words` of the word being completed
+    pub cword: usize,
+    /// the word being completed, with its quotes removed
+    pub current: &'a str,
+}
+
+impl CompSpec {
+    /// Parses the options shared by `complete` and `compgen`, the characters of `extra` being accepted
+    /// as additional flags.
+    ///
+    /// Returns the specification, the extra flags given and the operands.
+    pub fn parse(cmd: &str, args: &[String], extra: &str) -> Result<(Self, String, Vec<String>), String> {
+        let mut spec = Self::default();
+        let mut flags = String::new();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            if arg == "--" {
+                break;
+            }
+            if !arg.starts_with('-') || arg.len() == 1 {
+                let mut operands = vec![arg.clone()];
+                operands.extend(args.cloned());
+                return Ok((spec, flags, operands));
+            }
+
+            for opt in arg[1..].chars() {
+                match opt {
+                    'd' => spec.dirs = true,
+                    'f' => spec.files = true,
+                    'c' => spec.commands = true,
+                    'W' | 'F' => {
+                        let Some(value) = args.next() else {
+                            return Err(format!("{cmd}: -{opt}: option requires an argument"));
+                        };
+                        match opt {
+                            'W' => spec.words = Some(value.clone()),
+                            _ => spec.function = Some(value.clone()),
+                        }
+                    }
+                    _ if extra.contains(opt) => flags.push(opt),
+                    _ => return Err(format!("{cmd}: -{opt}: invalid option")),
+                }
+            }
+        }
+
+        Ok((spec, flags, args.cloned().collect()))
+    }
+
+    /// the candidates for `word` that don't depend on a function, sorted
+    pub fn generate(&self, word: &str, functions: &[String]) -> Vec<String> {
+        let mut candidates = Vec::new();
+
+        if let Some(words) = &self.words {
+            let words: BTreeSet<&str> = words.split_whitespace().filter(|w| w.starts_with(word)).collect();
+            candidates.extend(words.into_iter().map(String::from));
+        }
+        if self.commands {
+            candidates.extend(commands(word, functions));
+        }
+        if self.dirs || self.files {
+            candidates.extend(paths(word, !self.files));
+        }
+
+        candidates
+    }
+
+    /// the `complete` command setting this specification for `name`
+    pub fn to_command(&self, name: &str) -> String {
+        let mut command = String::from("complete");
+        for (set, flag) in [(self.dirs, "-d"), (self.files, "-f"), (self.commands, "-c")] {
+            if set {
+                command.push(' ');
+                command.push_str(flag);
+            }
+        }
+        if let Some(words) = &self.words {
+            command.push_str(&format!(" -W '{}'", words.replace('\'', "'\\''")));
+        }
+        if let Some(function) = &self.function {
+            command.push_str(&format!(" -F {function}"));
+        }
+        format!("{command} {name}")
+    }
+}
+
+/// builtins, `functions` and executables of the `PATH` starting with `prefix`, sorted
+pub fn commands(prefix: &str, functions: &[String]) -> Vec<String> {
+    let mut names: BTreeSet<String> = builtin::names()
+        .map(|name| name.to_string())
+        .chain(functions.iter().cloned())
+        .filter(|name| name.starts_with(prefix))
+        .collect();
+
+    let path = env::var("PATH").unwrap_or_default();
+    for dir in path.split(':').filter(|dir| !dir.is_empty()) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            let executable = fs::metadata(entry.path())
+                .is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0);
+            if name.starts_with(prefix) && executable {
+                names.insert(name);
+            }
+        }
+    }
+
+    names.into_iter().collect()
+}
+
+/// Files whose path starts with `word`, a leading `~` standing for the home directory,
+/// directories end with a `/`.
+pub fn paths(word: &str, dirs_only: bool) -> Vec<String> {
+    let (dir, prefix) = match word.rfind('/') {
+        Some(idx) => (&word[..=idx], &word[idx + 1..]),
+        None => ("", word),
+    };
+
+    let lookup = match dir.strip_prefix('~') {
+        Some(rest) => PathBuf::from(env::var("HOME").unwrap_or_default() + rest),
+        None if dir.is_empty() => PathBuf::from("."),
+        None => PathBuf::from(dir),
+    };
+
+    let Ok(entries) = fs::read_dir(&lookup) else {
+        return Vec::new();
+    };
+
+    let mut paths: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok().map(|name| (entry, name)))
+        // hidden files are only completed when asked for
+        .filter(|(_, name)| name.starts_with(prefix) && (prefix.starts_with('.') || !name.starts_with('.')))
+        .filter_map(|(entry, name)| {
+            let is_dir = fs::metadata(entry.path()).is_ok_and(|meta| meta.is_dir());
+            match is_dir {
+                true => Some(format!("{dir}{name}/")),
+                false if dirs_only => None,
+                false => Some(format!("{dir}{name}")),
+            }
+        })
+        .collect();
+
+    paths.sort();
+    paths
+}
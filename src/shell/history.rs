@@ -0,0 +1,353 @@
+use std::{
+    cell::RefCell,
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::PathBuf,
+};
+
+/// number of commands remembered when `HISTSIZE` isn't set
+const DEFAULT_SIZE: usize = 500;
+
+thread_local! {
+    static HISTORY: RefCell<History> = RefCell::new(History::default());
+}
+
+/// runs `f` with the history of the shell
+pub fn with_history<T>(f: impl FnOnce(&mut History) -> T) -> T {
+    HISTORY.with_borrow_mut(f)
+}
+
+/// The commands typed in an interactive shell, also saved in `HISTFILE` (`~/.0shell_history` by default).
+#[derive(Default)]
+pub struct History {
+    entries: Vec<String>,
+    /// number of entries dropped to stay within `HISTSIZE`, so the others keep their number
+    offset: usize,
+    /// only interactive shells record the commands they read
+    enabled: bool,
+    /// set while the command being read is recorded as the last entry
+    recording: bool,
+}
+
+impl History {
+    /// Loads the history file and starts recording commands.
+    pub fn load(&mut self) {
+        self.enabled = true;
+
+        let Some(path) = history_file() else {
+            return;
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return;
+        };
+
+        self.entries = content.lines().map(decode).collect();
+        let size = history_size();
+        if self.entries.len() > size {
+            self.entries.drain(..self.entries.len() - size);
+            // keeps the file from growing forever
+            let _ = self.write(None);
+        }
+    }
+
+    /// stops recording, for subshells which must leave the history file to their parent
+    pub fn disable(&mut self) {
+        self.enabled = false;
+        self.recording = false;
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// the commands of the history file, saved by this shell or others, or the entries when there is none
+    pub fn persisted(&self) -> Vec<String> {
+        history_file()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| content.lines().map(decode).collect())
+            .unwrap_or_else(|| self.entries.clone())
+    }
+
+    /// number of the first entry, as shown by `history`
+    pub fn first_number(&self) -> usize {
+        self.offset + 1
+    }
+
+    /// Records a command from its first line, unless `HISTCONTROL` asks to ignore lines starting with a space.
+    pub fn start(&mut self, line: &str) {
+        let line = line.trim_end_matches('\n');
+        self.recording = self.enabled && !(ignores("ignorespace") && line.starts_with(' '));
+        if !self.recording {
+            return;
+        }
+
+        self.entries.push(line.to_string());
+        let size = history_size();
+        if self.entries.len() > size {
+            let dropped = self.entries.len() - size;
+            self.entries.drain(..dropped);
+            self.offset += dropped;
+        }
+    }
+
+    /// adds a continuation line to the command being recorded
+    pub fn extend(&mut self, line: &str) {
+        if self.recording
+            && let Some(last) = self.entries.last_mut()
+        {
+            last.push('\n');
+            last.push_str(line.trim_end_matches('\n'));
+        }
+    }
+
+    /// Ends the command being recorded and appends it to the history file,
+    /// a duplicate of the previous entry is dropped when `HISTCONTROL` contains `ignoredups`.
+    pub fn finish(&mut self) {
+        if !std::mem::take(&mut self.recording) {
+            return;
+        }
+
+        if let [.., previous, last] = self.entries.as_slice()
+            && previous == last
+            && ignores("ignoredups")
+        {
+            self.entries.pop();
+            return;
+        }
+
+        if let Some(path) = history_file()
+            && let Some(entry) = self.entries.last()
+        {
+            // a single write in append mode doesn't get mixed with the ones of other shells
+            let _ = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| file.write_all(format!("{}\n", encode(entry)).as_bytes()));
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.offset = 0;
+    }
+
+    /// Removes the entry numbered `number`, a negative number counts from the end.
+    pub fn delete(&mut self, number: isize) -> Result<(), String> {
+        let idx = if number < 0 {
+            self.entries.len().checked_sub(number.unsigned_abs())
+        } else {
+            number.unsigned_abs().checked_sub(self.first_number())
+        };
+
+        match idx {
+            Some(idx) if idx < self.entries.len() => {
+                self.entries.remove(idx);
+                Ok(())
+            }
+            _ => Err(format!("{number}: history position out of range")),
+        }
+    }
+
+    /// Replaces the content of `path`, or of the history file, with the whole history.
+    pub fn write(&self, path: Option<PathBuf>) -> io::Result<()> {
+        let Some(path) = path.or_else(history_file) else {
+            return Ok(());
+        };
+
+        let content: String = self.entries.iter().map(|entry| encode(entry) + "\n").collect();
+
+        // other shells reading the file never see it half written
+        let tmp = path.with_extension(format!("tmp{}", std::process::id()));
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &path)
+    }
+
+    /// Expands the history references of `line`: `!!`, `!n`, `!-n`, `!prefix`, `!?text?`, `!$`,
+    /// and `^old^new` at the start of the line.
+    ///
+    /// Returns `None` when there was nothing to expand.
+    pub fn expand(&self, line: &str) -> Result<Option<String>, String> {
+        if let Some(rest) = line.strip_prefix('^') {
+            return self.quick_substitution(rest).map(Some);
+        }
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut out = String::new();
+        let mut expanded = false;
+        let mut single_quoted = false;
+        let mut double_quoted = false;
+
+        let mut i = 0;
+        while i < chars.len() {
+            let ch = chars[i];
+            match ch {
+                '\\' if !single_quoted => {
+                    out.push(ch);
+                    if let Some(&next) = chars.get(i + 1) {
+                        out.push(next);
+                        i += 1;
+                    }
+                }
+                '\'' if !double_quoted => {
+                    single_quoted = !single_quoted;
+                    out.push(ch);
+                }
+                '"' if !single_quoted => {
+                    double_quoted = !double_quoted;
+                    out.push(ch);
+                }
+                '!' if !single_quoted => match self.event(&chars[i + 1..], double_quoted)? {
+                    Some((text, len)) => {
+                        out.push_str(&text);
+                        expanded = true;
+                        i += len;
+                    }
+                    None => out.push(ch),
+                },
+                _ => out.push(ch),
+            }
+            i += 1;
+        }
+
+        Ok(expanded.then_some(out))
+    }
+
+    /// Finds the event referenced after a `!`, returns its text and the number of characters naming it.
+    fn event(&self, rest: &[char], double_quoted: bool) -> Result<Option<(String, usize)>, String> {
+        let Some(&first) = rest.first() else {
+            return Ok(None);
+        };
+        if first.is_whitespace() || first == '=' || first == '(' || (double_quoted && first == '"') {
+            return Ok(None);
+        }
+
+        let (found, len) = match first {
+            '!' => (self.entries.last().cloned(), 1),
+            '$' => {
+                let word = self.entries.last().and_then(|entry| entry.split_whitespace().last());
+                (word.map(String::from), 1)
+            }
+            '?' => {
+                let text: String = rest[1..].iter().take_while(|&&c| c != '?' && c != '\n').collect();
+                let len = 1 + text.chars().count();
+                let len = if rest.get(len) == Some(&'?') { len + 1 } else { len };
+                (self.entries.iter().rev().find(|entry| entry.contains(&text)).cloned(), len)
+            }
+            _ => {
+                let digits = rest
+                    .iter()
+                    .skip(usize::from(first == '-'))
+                    .take_while(|c| c.is_ascii_digit())
+                    .count();
+
+                if digits > 0 {
+                    let len = digits + usize::from(first == '-');
+                    let number: isize = rest[..len].iter().collect::<String>().parse().unwrap_or(0);
+                    (self.get(number).cloned(), len)
+                } else {
+                    let prefix: String = rest.iter().take_while(|&&c| !ends_event(c)).collect();
+                    let len = prefix.chars().count();
+                    (self.entries.iter().rev().find(|entry| entry.starts_with(&prefix)).cloned(), len)
+                }
+            }
+        };
+
+        match found {
+            Some(text) => Ok(Some((text, len))),
+            None => Err(format!("!{}: event not found", rest[..len].iter().collect::<String>())),
+        }
+    }
+
+    /// the entry numbered `number`, a negative number counts from the end
+    fn get(&self, number: isize) -> Option<&String> {
+        let idx = if number < 0 {
+            self.entries.len().checked_sub(number.unsigned_abs())?
+        } else {
+            number.unsigned_abs().checked_sub(self.first_number())?
+        };
+        self.entries.get(idx)
+    }
+
+    /// `^old^new^` repeats the previous command with the first `old` replaced by `new`
+    fn quick_substitution(&self, rest: &str) -> Result<String, String> {
+        let rest = rest.trim_end_matches('\n');
+        let (old, rest) = rest.split_once('^').unwrap_or((rest, ""));
+        let (new, tail) = rest.split_once('^').unwrap_or((rest, ""));
+
+        let previous = self.entries.last().ok_or_else(|| "!!: event not found".to_string())?;
+        if old.is_empty() || !previous.contains(old) {
+            return Err(format!(":s^{old}^{new}^: substitution failed"));
+        }
+
+        Ok(format!("{}{tail}\n", previous.replacen(old, new, 1)))
+    }
+}
+
+/// characters ending the prefix of a `!prefix` reference
+fn ends_event(ch: char) -> bool {
+    ch.is_whitespace() || ";&|()<>\"'`:".contains(ch)
+}
+
+/// whether `HISTCONTROL` contains `option`
+fn ignores(option: &str) -> bool {
+    env::var("HISTCONTROL")
+        .unwrap_or_default()
+        .split(':')
+        .any(|opt| opt == option || opt == "ignoreboth")
+}
+
+/// An entry on a line of its own in the history file: its backslashes are doubled and its newlines
+/// written as `\n`.
+fn encode(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+/// the entry saved as `line` by [`encode`]
+fn decode(line: &str) -> String {
+    let mut entry = String::with_capacity(line.len());
+    let mut chars = line.chars().peekable();
+    while let Some(ch) = chars.next() {
+        let escaped = match ch {
+            '\\' => chars.next_if(|next| matches!(next, 'n' | '\\')),
+            _ => None,
+        };
+        entry.push(match escaped {
+            Some('n') => '\n',
+            Some(_) => '\\',
+            None => ch,
+        });
+    }
+    entry
+}
+
+fn history_size() -> usize {
+    env::var("HISTSIZE")
+        .ok()
+        .and_then(|size| size.parse().ok())
+        .unwrap_or(DEFAULT_SIZE)
+}
+
+fn history_file() -> Option<PathBuf> {
+    match env::var("HISTFILE") {
+        Ok(file) if file.is_empty() => None,
+        Ok(file) => Some(PathBuf::from(file)),
+        Err(_) => env::var("HOME").ok().map(|home| PathBuf::from(home).join(".0shell_history")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entries_saved_on_a_line() {
+        for entry in ["ls -l", "f() {\n  echo \"a\\nb\"\n}", "echo a \\\nb", "\\", "x\\"] {
+            let line = encode(entry);
+            assert!(!line.contains('\n'));
+            assert_eq!(decode(&line), entry);
+        }
+        assert_eq!(encode("a\nb\\n"), "a\\nb\\\\n");
+    }
+}
@@ -0,0 +1,630 @@
+use libc::{
+    SIGCONT, SIGINT, STDIN_FILENO, TCSADRAIN, WCONTINUED, WEXITSTATUS, WIFCONTINUED, WIFEXITED, WIFSIGNALED,
+    WIFSTOPPED, WNOHANG, WSTOPSIG, WTERMSIG, WUNTRACED, c_int, getpgrp, getpid, isatty, kill, killpg,
+    pid_t, setpgid, tcgetattr, tcgetpgrp, tcsetattr, tcsetpgrp, termios, waitpid,
+};
+use std::{io::Error, io::ErrorKind, mem::MaybeUninit};
+
+use super::signals::{ignore_job_control_signals, signal_to_name};
+use crate::utils::process::status_code;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobStatus {
+    Running,
+    /// stopped by the given signal
+    Stopped(c_int),
+    /// raw status as reported by `waitpid`
+    Done(c_int),
+}
+
+pub struct Process {
+    pub pid: pid_t,
+    pub status: JobStatus,
+}
+
+/// A pipeline started by the shell, all of its processes share the process group `pgid`.
+pub struct Job {
+    pub id: usize,
+    pub pgid: pid_t,
+    pub processes: Vec<Process>,
+    pub command: String,
+    /// whether the last change of status has already been reported
+    pub notified: bool,
+}
+
+impl Job {
+    pub fn status(&self) -> JobStatus {
+        let statuses = self.processes.iter().map(|p| p.status);
+        if statuses.clone().any(|s| s == JobStatus::Running) {
+            return JobStatus::Running;
+        }
+
+        if let Some(stopped) = statuses.clone().find(|s| matches!(s, JobStatus::Stopped(_))) {
+            return stopped;
+        }
+
+        self.processes.last().map_or(JobStatus::Done(0), |p| p.status)
+    }
+
+    /// the process id of the last command of the pipeline, as expanded by `$!`
+    pub fn pid(&self) -> pid_t {
+        self.processes.last().map_or(self.pgid, |p| p.pid)
+    }
+
+    /// a human readable description of the job status, as printed by `jobs` and notifications
+    pub fn describe(&self) -> String {
+        match self.status() {
+            JobStatus::Running => "Running".into(),
+            JobStatus::Stopped(_) => "Stopped".into(),
+            JobStatus::Done(status) if WIFEXITED(status) => match WEXITSTATUS(status) {
+                0 => "Done".into(),
+                code => format!("Exit {code}"),
+            },
+            JobStatus::Done(status) if WIFSIGNALED(status) => signal_name(WTERMSIG(status)),
+            JobStatus::Done(_) => "Done".into(),
+        }
+    }
+
+    /// Exit status of the pipeline, which is the one of its last command,
+    /// or with `pipefail` the one of its last failing command.
+    pub fn exit_status(&self, pipefail: bool) -> i32 {
+        match self.status() {
+            JobStatus::Running => 0,
+            JobStatus::Stopped(sig) => 128 + sig,
+            JobStatus::Done(_) if pipefail => self
+                .processes
+                .iter()
+                .rev()
+                .filter_map(|p| match p.status {
+                    JobStatus::Done(status) => Some(status_code(status)),
+                    _ => None,
+                })
+                .find(|&code| code != 0)
+                .unwrap_or(0),
+            JobStatus::Done(status) => status_code(status),
+        }
+    }
+
+    fn update(&mut self, pid: pid_t, status: c_int) {
+        let Some(process) = self.processes.iter_mut().find(|p| p.pid == pid) else {
+            return;
+        };
+
+        process.status = if WIFSTOPPED(status) {
+            JobStatus::Stopped(WSTOPSIG(status))
+        } else if WIFCONTINUED(status) {
+            JobStatus::Running
+        } else {
+            JobStatus::Done(status)
+        };
+        self.notified = false;
+    }
+}
+
+/// Jobs started by the shell, identified by their job number.
+#[derive(Default)]
+pub struct JobTable {
+    jobs: Vec<Job>,
+    /// set when the shell owns a terminal and manages process groups
+    job_control: bool,
+    shell_pgid: pid_t,
+    /// terminal modes restored whenever the shell takes the terminal back
+    shell_tmodes: Option<termios>,
+    /// the `pipefail` option, deciding the exit status of the jobs
+    pub pipefail: bool,
+    /// processes of the jobs removed by `disown` before they finished, reaped all the same
+    disowned: Vec<pid_t>,
+}
+
+impl JobTable {
+    /// Puts the shell in its own process group in the foreground of the terminal, does nothing
+    /// if the shell isn't `interactive` or standard input is not a terminal: a script run in
+    /// the background leaves the terminal and its signals alone.
+    pub fn enable_job_control(&mut self, interactive: bool) {
+        if !interactive || unsafe { isatty(STDIN_FILENO) } == 0 {
+            return;
+        }
+
+        unsafe {
+            // wait until the shell has been put in the foreground
+            loop {
+                let pgrp = getpgrp();
+                if tcgetpgrp(STDIN_FILENO) == pgrp {
+                    break;
+                }
+                kill(-pgrp, libc::SIGTTIN);
+            }
+
+            ignore_job_control_signals();
+            self.shell_pgid = getpid();
+            if setpgid(self.shell_pgid, self.shell_pgid) == -1 && getpgrp() != self.shell_pgid {
+                eprintln!("0-shell: cannot set process group: {}", Error::last_os_error());
+                return;
+            }
+            tcsetpgrp(STDIN_FILENO, self.shell_pgid);
+
+            let mut tmodes = MaybeUninit::<termios>::uninit();
+            if tcgetattr(STDIN_FILENO, tmodes.as_mut_ptr()) == 0 {
+                self.shell_tmodes = Some(tmodes.assume_init());
+            }
+        }
+
+        self.job_control = true;
+    }
+
+    pub fn job_control(&self) -> bool {
+        self.job_control
+    }
+
+    /// Called in a freshly forked child of a pipeline: joins the process group `pgid`
+    /// (or creates it when `pgid` is 0) and takes the terminal if it runs in the foreground.
+    pub fn enter_process_group(&self, pgid: pid_t, foreground: bool) {
+        if self.job_control {
+            unsafe {
+                let pid = getpid();
+                let pgid = if pgid == 0 { pid } else { pgid };
+                setpgid(pid, pgid);
+                if foreground {
+                    tcsetpgrp(STDIN_FILENO, pgid);
+                }
+            }
+        }
+    }
+
+    /// Called by the shell after forking `pid`, the same as [`JobTable::enter_process_group`]
+    /// from the parent side so both agree whoever runs first.
+    pub fn set_process_group(&self, pid: pid_t, pgid: pid_t) {
+        if self.job_control {
+            unsafe { setpgid(pid, if pgid == 0 { pid } else { pgid }) };
+        }
+    }
+
+    /// registers a new job and returns its job number
+    pub fn add(&mut self, pgid: pid_t, pids: Vec<pid_t>, command: String) -> usize {
+        let id = self.jobs.iter().map(|job| job.id).max().unwrap_or(0) + 1;
+        let processes = pids
+            .into_iter()
+            .map(|pid| Process {
+                pid,
+                status: JobStatus::Running,
+            })
+            .collect();
+
+        self.jobs.push(Job {
+            id,
+            pgid,
+            processes,
+            command,
+            notified: true,
+        });
+        id
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Job> {
+        let idx = self.jobs.iter().position(|job| job.id == id)?;
+        Some(self.jobs.remove(idx))
+    }
+
+    /// Removes a job from the table without waiting for it, its processes are still reaped
+    /// so they don't linger as zombies.
+    pub fn disown(&mut self, id: usize) {
+        if let Some(job) = self.remove(id) {
+            let running = job.processes.iter().filter(|p| !matches!(p.status, JobStatus::Done(_)));
+            self.disowned.extend(running.map(|p| p.pid));
+        }
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    fn get_mut(&mut self, id: usize) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Job> {
+        self.jobs.iter()
+    }
+
+    /// jobs ordered from the least to the most likely to be meant by `%%`,
+    /// stopped jobs take precedence over running ones
+    fn ranked(&self) -> Vec<&Job> {
+        let mut ranked: Vec<&Job> = self.jobs.iter().collect();
+        ranked.sort_by_key(|job| matches!(job.status(), JobStatus::Stopped(_)));
+        ranked
+    }
+
+    /// the job that `%%` and `%+` refer to
+    pub fn current(&self) -> Option<&Job> {
+        self.ranked().last().copied()
+    }
+
+    /// the job that `%-` refers to
+    pub fn previous(&self) -> Option<&Job> {
+        self.ranked().iter().rev().nth(1).copied()
+    }
+
+    pub fn by_pid(&self, pid: pid_t) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.processes.iter().any(|p| p.pid == pid))
+    }
+
+    /// Resolves a job specification: `%n`, `%%`, `%+`, `%-`, `%prefix` or `%?substring`.
+    pub fn find(&self, spec: &str) -> Result<&Job, String> {
+        let Some(spec) = spec.strip_prefix('%') else {
+            return Err(format!("{spec}: no such job"));
+        };
+
+        let job = match spec {
+            "" | "%" | "+" => self.current(),
+            "-" => self.previous(),
+            _ if spec.chars().all(|ch| ch.is_ascii_digit()) => {
+                let id = spec.parse::<usize>().unwrap_or(0);
+                self.get(id)
+            }
+            _ => {
+                let matches: Vec<&Job> = match spec.strip_prefix('?') {
+                    Some(substring) => self.jobs.iter().filter(|job| job.command.contains(substring)).collect(),
+                    None => self.jobs.iter().filter(|job| job.command.starts_with(spec)).collect(),
+                };
+                if matches.len() > 1 {
+                    return Err(format!("%{spec}: ambiguous job spec"));
+                }
+                matches.first().copied()
+            }
+        };
+
+        job.ok_or_else(|| format!("%{spec}: no such job"))
+    }
+
+    /// Resolves the job operand of `fg`, `bg` and `disown`, defaulting to the current job.
+    pub fn find_or_current(&self, spec: Option<&String>) -> Result<&Job, String> {
+        match spec {
+            Some(spec) if spec.starts_with('%') => self.find(spec),
+            Some(spec) => match spec.parse::<pid_t>().ok().and_then(|pid| self.by_pid(pid)) {
+                Some(job) => Ok(job),
+                None => Err(format!("{spec}: no such job")),
+            },
+            None => self.current().ok_or_else(|| "current: no such job".to_string()),
+        }
+    }
+
+    /// the `+`/`-` marker printed next to the current and previous jobs
+    pub fn marker(&self, id: usize) -> char {
+        match (self.current(), self.previous()) {
+            (Some(job), _) if job.id == id => '+',
+            (_, Some(job)) if job.id == id => '-',
+            _ => ' ',
+        }
+    }
+
+    /// the line describing a job as printed by `jobs` and notifications,
+    /// `long` adds the process group id
+    pub fn format(&self, job: &Job, long: bool) -> String {
+        let pgid = if long { format!(" {}", job.pgid) } else { " ".into() };
+        let background = if job.status() == JobStatus::Running { " &" } else { "" };
+        format!(
+            "[{}]{}{} {:<24}{}{}",
+            job.id,
+            self.marker(job.id),
+            pgid,
+            job.describe(),
+            job.command,
+            background
+        )
+    }
+
+    /// collects the status changes of every job, and the disowned processes that ended, without blocking
+    pub fn reap(&mut self) {
+        self.disowned.retain(|&pid| {
+            let mut status: c_int = 0;
+            unsafe { waitpid(pid, &mut status, WNOHANG) == 0 }
+        });
+
+        for job in &mut self.jobs {
+            let pids: Vec<pid_t> = job
+                .processes
+                .iter()
+                .filter(|p| !matches!(p.status, JobStatus::Done(_)))
+                .map(|p| p.pid)
+                .collect();
+
+            for pid in pids {
+                let mut status: c_int = 0;
+                let flags = WNOHANG | WUNTRACED | WCONTINUED;
+                if unsafe { waitpid(pid, &mut status, flags) } == pid {
+                    job.update(pid, status);
+                }
+            }
+        }
+    }
+
+    /// Blocks until every process of the job changed its status,
+    /// when `stop` is set a stopped process counts as a change.
+    fn wait_processes(&mut self, id: usize, stop: bool) {
+        let Some(job) = self.get_mut(id) else {
+            return;
+        };
+
+        let pids: Vec<pid_t> = job
+            .processes
+            .iter()
+            .filter(|p| !matches!(p.status, JobStatus::Done(_)))
+            .map(|p| p.pid)
+            .collect();
+
+        for pid in pids {
+            let mut status: c_int = 0;
+            loop {
+                let wid = unsafe { waitpid(pid, &mut status, if stop { WUNTRACED } else { 0 }) };
+                if wid == pid {
+                    job.update(pid, status);
+                    break;
+                }
+                if wid == -1 && Error::last_os_error().kind() == ErrorKind::Interrupted {
+                    continue;
+                }
+
+                // not our child anymore, nothing to wait for
+                job.update(pid, 0);
+                break;
+            }
+        }
+    }
+
+    /// Blocks until the job is finished and removes it from the table, returns its exit status.
+    pub fn wait(&mut self, id: usize) -> i32 {
+        self.wait_processes(id, false);
+        self.remove(id).map_or(127, |job| job.exit_status(self.pipefail))
+    }
+
+    /// Gives the terminal to the job and waits for it to finish or stop,
+    /// a stopped job is kept in the table and reported.
+    ///
+    /// When `resume` is set the job is sent `SIGCONT` first.
+    pub fn foreground(&mut self, id: usize, resume: bool) -> i32 {
+        let Some(job) = self.get_mut(id) else {
+            return 127;
+        };
+        let pgid = job.pgid;
+
+        if resume {
+            for process in &mut job.processes {
+                if let JobStatus::Stopped(_) = process.status {
+                    process.status = JobStatus::Running;
+                }
+            }
+        }
+
+        if self.job_control {
+            unsafe { tcsetpgrp(STDIN_FILENO, pgid) };
+        }
+        if resume && let Some(job) = self.get(id) {
+            let _ = self.signal(job, SIGCONT);
+        }
+
+        self.wait_processes(id, true);
+        self.take_terminal();
+
+        let Some(job) = self.get_mut(id) else {
+            return 127;
+        };
+        if let JobStatus::Stopped(sig) = job.status() {
+            job.notified = true;
+            let line = self.get(id).map(|job| self.format(job, false)).unwrap_or_default();
+            eprintln!("\n{line}");
+            return 128 + sig;
+        }
+
+        let job = self.remove(id);
+        let exit_status = job.map_or(127, |job| job.exit_status(self.pipefail));
+        // the prompt starts on a new line after an interrupted job
+        if exit_status == 128 + SIGINT {
+            eprintln!();
+        }
+        exit_status
+    }
+
+    /// Continues a stopped job in the background.
+    pub fn resume_background(&mut self, id: usize) {
+        let Some(job) = self.get_mut(id) else {
+            return;
+        };
+
+        for process in &mut job.processes {
+            if let JobStatus::Stopped(_) = process.status {
+                process.status = JobStatus::Running;
+            }
+        }
+        if let Some(job) = self.get(id) {
+            let _ = self.signal(job, SIGCONT);
+        }
+    }
+
+    /// Sends `sig` to the processes of the job: to its process group with job control, otherwise
+    /// they are in the group of the shell and get it one by one.
+    pub fn signal(&self, job: &Job, sig: c_int) -> Result<(), Error> {
+        if self.job_control {
+            return match unsafe { killpg(job.pgid, sig) } {
+                -1 => Err(Error::last_os_error()),
+                _ => Ok(()),
+            };
+        }
+
+        let mut result = Ok(());
+        for process in job.processes.iter().filter(|p| !matches!(p.status, JobStatus::Done(_))) {
+            if unsafe { kill(process.pid, sig) } == -1 {
+                result = Err(Error::last_os_error());
+            }
+        }
+        result
+    }
+
+    /// puts the shell back in the foreground of the terminal with its own terminal modes
+    fn take_terminal(&self) {
+        if !self.job_control {
+            return;
+        }
+
+        unsafe {
+            tcsetpgrp(STDIN_FILENO, self.shell_pgid);
+            if let Some(tmodes) = &self.shell_tmodes {
+                tcsetattr(STDIN_FILENO, TCSADRAIN, tmodes);
+            }
+        }
+    }
+
+    /// Forgets the finished jobs and marks the stopped ones as reported, once they have been listed.
+    pub fn forget_reported(&mut self) {
+        self.jobs.retain(|job| !matches!(job.status(), JobStatus::Done(_)));
+        for job in &mut self.jobs {
+            job.notified = true;
+        }
+    }
+
+    /// Reports the jobs that finished or stopped since the last call and forgets the finished ones.
+    pub fn notify(&mut self) {
+        self.reap();
+
+        let changed: Vec<usize> = self
+            .jobs
+            .iter()
+            .filter(|job| !job.notified && job.status() != JobStatus::Running)
+            .map(|job| job.id)
+            .collect();
+
+        for id in changed {
+            let line = self.get(id).map(|job| self.format(job, false)).unwrap_or_default();
+            eprintln!("{line}");
+
+            match self.get_mut(id) {
+                Some(job) if matches!(job.status(), JobStatus::Done(_)) => {
+                    self.remove(id);
+                }
+                Some(job) => job.notified = true,
+                None => {}
+            }
+        }
+    }
+}
+
+/// the description of a signal as given by `strsignal`
+pub fn signal_name(signal: c_int) -> String {
+    let description = unsafe { libc::strsignal(signal) };
+    if description.is_null() {
+        return signal_to_name(signal).map_or_else(|| format!("Signal {signal}"), |name| format!("SIG{name}"));
+    }
+
+    let description = unsafe { std::ffi::CStr::from_ptr(description) };
+    description.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libc::{SIGKILL, SIGTSTP};
+
+    /// raw statuses, as `waitpid` reports them
+    fn exited(code: c_int) -> c_int {
+        code << 8
+    }
+
+    fn stopped(sig: c_int) -> c_int {
+        (sig << 8) | 0x7f
+    }
+
+    fn table() -> JobTable {
+        let mut jobs = JobTable::default();
+        jobs.add(100, vec![100, 101], "sleep 10 | cat".into());
+        jobs.add(200, vec![200], "make all".into());
+        jobs.add(300, vec![300], "sleep 20".into());
+        jobs
+    }
+
+    fn update(jobs: &mut JobTable, id: usize, pid: pid_t, status: c_int) {
+        jobs.get_mut(id).unwrap().update(pid, status);
+    }
+
+    #[test]
+    fn job_numbers() {
+        let mut jobs = table();
+        assert_eq!(jobs.iter().map(|job| job.id).collect::<Vec<_>>(), [1, 2, 3]);
+        jobs.remove(2);
+        // the numbers of the others don't change, a new job follows the highest one
+        assert_eq!(jobs.add(400, vec![400], "vi".into()), 4);
+        jobs.remove(4);
+        jobs.remove(3);
+        assert_eq!(jobs.add(500, vec![500], "top".into()), 2);
+    }
+
+    #[test]
+    fn specifications() {
+        let jobs = table();
+        let id = |spec: &str| jobs.find(spec).map(|job| job.id);
+        assert_eq!(id("%2"), Ok(2));
+        assert_eq!(id("%%"), Ok(3));
+        assert_eq!(id("%+"), Ok(3));
+        assert_eq!(id("%-"), Ok(2));
+        assert_eq!(id("%make"), Ok(2));
+        assert_eq!(id("%?20"), Ok(3));
+        assert_eq!(id("%sleep"), Err("%sleep: ambiguous job spec".into()));
+        assert_eq!(id("%9"), Err("%9: no such job".into()));
+        assert_eq!(id("2"), Err("2: no such job".into()));
+        assert_eq!(jobs.find_or_current(Some(&"101".into())).map(|job| job.id), Ok(1));
+    }
+
+    #[test]
+    fn stopped_jobs_are_current() {
+        let mut jobs = table();
+        update(&mut jobs, 1, 101, stopped(SIGTSTP));
+        // a process still running keeps the job running
+        assert!(jobs.get(1).unwrap().status() == JobStatus::Running);
+        update(&mut jobs, 1, 100, stopped(SIGTSTP));
+        assert!(jobs.get(1).unwrap().status() == JobStatus::Stopped(SIGTSTP));
+        assert_eq!(jobs.current().map(|job| job.id), Some(1));
+        assert_eq!(jobs.previous().map(|job| job.id), Some(3));
+        assert_eq!((jobs.marker(1), jobs.marker(3), jobs.marker(2)), ('+', '-', ' '));
+        assert_eq!(jobs.format(jobs.get(1).unwrap(), false), "[1]+  Stopped                 sleep 10 | cat");
+        assert_eq!(jobs.format(jobs.get(2).unwrap(), true), "[2]  200 Running                 make all &");
+    }
+
+    #[test]
+    // the child is waited for by the job table
+    #[allow(clippy::zombie_processes)]
+    fn disowned_processes_reaped() {
+        let child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id() as pid_t;
+        let mut jobs = JobTable::default();
+        let id = jobs.add(pid, vec![pid], "true".into());
+        jobs.disown(id);
+        assert!(jobs.iter().next().is_none());
+
+        for _ in 0..100 {
+            jobs.reap();
+            if jobs.disowned.is_empty() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(jobs.disowned.is_empty());
+        // no zombie is left behind
+        assert!(!std::path::Path::new(&format!("/proc/{pid}")).exists());
+    }
+
+    #[test]
+    fn statuses() {
+        let mut jobs = table();
+        update(&mut jobs, 1, 100, exited(3));
+        update(&mut jobs, 1, 101, exited(0));
+        let job = jobs.get(1).unwrap();
+        assert_eq!(job.describe(), "Done");
+        assert_eq!(job.exit_status(false), 0);
+        // with `pipefail` the last failing command decides
+        assert_eq!(job.exit_status(true), 3);
+
+        update(&mut jobs, 2, 200, exited(2));
+        assert_eq!(jobs.get(2).unwrap().describe(), "Exit 2");
+        update(&mut jobs, 3, 300, SIGKILL);
+        assert_eq!(jobs.get(3).unwrap().exit_status(false), 128 + SIGKILL);
+        assert_eq!(jobs.get(3).unwrap().describe(), "Killed");
+    }
+}
@@ -0,0 +1,10 @@
+pub mod aliases;
+pub mod completion;
+pub mod history;
+pub mod jobs;
+pub mod options;
+pub mod path;
+pub mod signals;
+pub mod state;
+
+pub use state::ShellState;
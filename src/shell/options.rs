@@ -0,0 +1,110 @@
+use std::{
+    io::{Write, stderr},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+/// `verbose` for the readers of continuation lines, which have no access to the state of the shell
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// The options changed with `set`, all off by default.
+#[derive(Clone, Copy, Default)]
+pub struct ShellOptions {
+    /// `-e`: the shell exits when a command fails
+    pub errexit: bool,
+    /// an interactive shell doesn't exit at the end of its input
+    pub ignoreeof: bool,
+    /// `-C`: `>` doesn't overwrite existing files
+    pub noclobber: bool,
+    /// `-f`: pathname expansion is disabled
+    pub noglob: bool,
+    /// `-u`: expanding an unset parameter is an error
+    pub nounset: bool,
+    /// the status of a pipeline is the one of its last failing command
+    pub pipefail: bool,
+    /// `-v`: input lines are written to standard error as they are read
+    pub verbose: bool,
+    /// `-x`: commands are written to standard error before they run
+    pub xtrace: bool,
+    /// `echo` expands backslash escapes without `-e`
+    pub xpg_echo: bool,
+}
+
+/// names of the options, with the letter setting them when they have one
+pub const OPTIONS: &[(&str, Option<char>)] = &[
+    ("errexit", Some('e')),
+    ("ignoreeof", None),
+    ("noclobber", Some('C')),
+    ("noglob", Some('f')),
+    ("nounset", Some('u')),
+    ("pipefail", None),
+    ("verbose", Some('v')),
+    ("xtrace", Some('x')),
+    ("xpg_echo", None),
+];
+
+impl ShellOptions {
+    /// whether the option `name` is set, `None` for an unknown option
+    pub fn get(&self, name: &str) -> Option<bool> {
+        let set = match name {
+            "errexit" => self.errexit,
+            "ignoreeof" => self.ignoreeof,
+            "noclobber" => self.noclobber,
+            "noglob" => self.noglob,
+            "nounset" => self.nounset,
+            "pipefail" => self.pipefail,
+            "verbose" => self.verbose,
+            "xtrace" => self.xtrace,
+            "xpg_echo" => self.xpg_echo,
+            _ => return None,
+        };
+        Some(set)
+    }
+
+    /// Sets or unsets the option `name`, returns `false` for an unknown option.
+    pub fn set(&mut self, name: &str, on: bool) -> bool {
+        let option = match name {
+            "errexit" => &mut self.errexit,
+            "ignoreeof" => &mut self.ignoreeof,
+            "noclobber" => &mut self.noclobber,
+            "noglob" => &mut self.noglob,
+            "nounset" => &mut self.nounset,
+            "pipefail" => &mut self.pipefail,
+            "verbose" => &mut self.verbose,
+            "xtrace" => &mut self.xtrace,
+            "xpg_echo" => &mut self.xpg_echo,
+            _ => return false,
+        };
+        *option = on;
+        true
+    }
+
+    /// the letters of the options set, as expanded by `$-`
+    pub fn flags(&self) -> String {
+        OPTIONS
+            .iter()
+            .filter(|(name, _)| self.get(name) == Some(true))
+            .filter_map(|(_, letter)| *letter)
+            .collect()
+    }
+}
+
+/// the name of the option set by the letter `flag`
+pub fn option_name(flag: char) -> Option<&'static str> {
+    OPTIONS.iter().find(|(_, letter)| *letter == Some(flag)).map(|(name, _)| *name)
+}
+
+/// whether input lines are echoed, see [`ShellOptions::verbose`]
+pub fn verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+pub fn set_verbose(on: bool) {
+    VERBOSE.store(on, Ordering::Relaxed);
+}
+
+/// writes an input line to standard error with the `verbose` option
+pub fn echo_verbose(line: &str) {
+    if verbose() {
+        let _ = writeln!(stderr(), "{}", line.trim_end_matches('\n'));
+    }
+}
@@ -0,0 +1,87 @@
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    os::unix::fs::PermissionsExt,
+    path::{Path, PathBuf},
+};
+
+/// The executables already looked up in `PATH`, shown and changed by `hash`.
+#[derive(Default)]
+pub struct Hashed {
+    entries: BTreeMap<String, Entry>,
+}
+
+pub struct Entry {
+    pub path: PathBuf,
+    /// how many times it was run
+    pub hits: usize,
+}
+
+impl Hashed {
+    /// The executable `name` runs: the one remembered, or the first one found in `PATH`
+    /// which is then remembered. `hit` counts it as run.
+    ///
+    /// A name containing a `/` isn't remembered, nor is an executable that no longer exists.
+    pub fn find(&mut self, name: &str, hit: bool) -> Option<PathBuf> {
+        if name.contains('/') {
+            return find(name);
+        }
+        if self.entries.get(name).is_none_or(|entry| !is_executable(&entry.path)) {
+            self.entries.remove(name);
+            let path = find(name)?;
+            self.entries.insert(name.to_string(), Entry { path, hits: 0 });
+        }
+
+        let entry = self.entries.get_mut(name)?;
+        if hit {
+            entry.hits += 1;
+        }
+        Some(entry.path.clone())
+    }
+
+    /// the path remembered for `name`
+    pub fn get(&self, name: &str) -> Option<&Path> {
+        self.entries.get(name).map(|entry| entry.path.as_path())
+    }
+
+    /// remembers `path` as the executable `name` runs
+    pub fn insert(&mut self, name: &str, path: PathBuf) {
+        self.entries.insert(name.to_string(), Entry { path, hits: 0 });
+    }
+
+    /// forgets all the paths, when `PATH` changes
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &Entry)> {
+        self.entries.iter()
+    }
+}
+
+/// The executables named `name` in the directories of `PATH`, in their order.
+/// A name containing a `/` isn't looked up, it's the path of the executable itself.
+pub fn search(name: &str) -> Vec<PathBuf> {
+    if name.contains('/') {
+        return Some(PathBuf::from(name)).filter(|path| is_executable(path)).into_iter().collect();
+    }
+    if name.is_empty() {
+        return Vec::new();
+    }
+
+    let path = env::var("PATH").unwrap_or_default();
+    path.split(':')
+        // an empty directory stands for the current one
+        .map(|dir| Path::new(if dir.is_empty() { "." } else { dir }).join(name))
+        .filter(|path| is_executable(path))
+        .collect()
+}
+
+/// the executable `name` runs, the first one found in `PATH`
+pub fn find(name: &str) -> Option<PathBuf> {
+    search(name).into_iter().next()
+}
+
+fn is_executable(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|meta| meta.is_file() && meta.permissions().mode() & 0o111 != 0)
+}
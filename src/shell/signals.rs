@@ -0,0 +1,136 @@
+use libc::{
+    SIG_DFL, SIG_IGN, SIGINT, SIGKILL, SIGQUIT, SIGSTOP, SIGTSTP, SIGTTIN, SIGTTOU, c_int,
+    sigaction, sigemptyset, signal,
+};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// signals known by name, as accepted by `kill` and `trap`
+pub const SIGNALS: &[(c_int, &str)] = &[
+    (libc::SIGHUP, "HUP"),
+    (libc::SIGINT, "INT"),
+    (libc::SIGQUIT, "QUIT"),
+    (libc::SIGILL, "ILL"),
+    (libc::SIGTRAP, "TRAP"),
+    (libc::SIGABRT, "ABRT"),
+    (libc::SIGBUS, "BUS"),
+    (libc::SIGFPE, "FPE"),
+    (libc::SIGKILL, "KILL"),
+    (libc::SIGUSR1, "USR1"),
+    (libc::SIGSEGV, "SEGV"),
+    (libc::SIGUSR2, "USR2"),
+    (libc::SIGPIPE, "PIPE"),
+    (libc::SIGALRM, "ALRM"),
+    (libc::SIGTERM, "TERM"),
+    (libc::SIGCHLD, "CHLD"),
+    (libc::SIGCONT, "CONT"),
+    (libc::SIGSTOP, "STOP"),
+    (libc::SIGTSTP, "TSTP"),
+    (libc::SIGTTIN, "TTIN"),
+    (libc::SIGTTOU, "TTOU"),
+    (libc::SIGURG, "URG"),
+    (libc::SIGXCPU, "XCPU"),
+    (libc::SIGXFSZ, "XFSZ"),
+    (libc::SIGVTALRM, "VTALRM"),
+    (libc::SIGPROF, "PROF"),
+    (libc::SIGWINCH, "WINCH"),
+    (libc::SIGIO, "IO"),
+    (libc::SIGSYS, "SYS"),
+];
+
+/// signals received by [`catch`] that haven't been handled yet, indexed by signal number
+static PENDING: [AtomicBool; 65] = [const { AtomicBool::new(false) }; 65];
+
+/// Converts a signal name (`TERM`, `SIGTERM`, case insensitive) or number into its number.
+pub fn signal_number(name: &str) -> Option<c_int> {
+    if let Ok(number) = name.parse::<c_int>() {
+        return SIGNALS.iter().find(|(sig, _)| *sig == number).map(|(sig, _)| *sig);
+    }
+
+    let name = name.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+    SIGNALS.iter().find(|(_, n)| *n == name).map(|(sig, _)| *sig)
+}
+
+pub fn signal_to_name(signal: c_int) -> Option<&'static str> {
+    SIGNALS.iter().find(|(sig, _)| *sig == signal).map(|(_, name)| *name)
+}
+
+extern "C" fn on_signal(sig: c_int) {
+    if let Some(pending) = PENDING.get(sig as usize) {
+        pending.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Records `sig` as pending instead of acting upon it.
+///
+/// The handler is installed without `SA_RESTART`, so a blocking read is interrupted
+/// and the shell gets a chance to react.
+pub fn catch(sig: c_int) {
+    unsafe {
+        let mut action: sigaction = std::mem::zeroed();
+        action.sa_sigaction = on_signal as *const () as usize;
+        action.sa_flags = 0;
+        sigemptyset(&mut action.sa_mask);
+        sigaction(sig, &action, std::ptr::null_mut());
+    }
+}
+
+pub fn ignore(sig: c_int) {
+    unsafe { signal(sig, SIG_IGN) };
+}
+
+/// Restores the disposition a signal has when it isn't trapped,
+/// an interactive shell must not be interrupted, quit or stopped from the terminal.
+pub fn restore(sig: c_int, interactive: bool) {
+    match sig {
+        SIGINT if interactive => catch(sig),
+        SIGQUIT | SIGTSTP | SIGTTIN | SIGTTOU if interactive => ignore(sig),
+        _ => unsafe {
+            signal(sig, SIG_DFL);
+        },
+    }
+}
+
+/// sets up the dispositions of an interactive shell
+pub fn init_interactive() {
+    for sig in [SIGINT, SIGQUIT, SIGTSTP, SIGTTIN, SIGTTOU] {
+        restore(sig, true);
+    }
+}
+
+/// an interactive shell must not be stopped by the job control signals
+pub fn ignore_job_control_signals() {
+    for sig in [SIGTSTP, SIGTTIN, SIGTTOU] {
+        ignore(sig);
+    }
+}
+
+/// Restores the default dispositions in a child except for the signals in `ignored`,
+/// ignored signals would be inherited by programs.
+pub fn reset_signals(ignored: &[c_int]) {
+    for (sig, _) in SIGNALS {
+        if matches!(*sig, SIGKILL | SIGSTOP) || ignored.contains(sig) {
+            continue;
+        }
+        unsafe { signal(*sig, SIG_DFL) };
+    }
+    take_pending();
+}
+
+/// Records `sig` as received, for Ctrl-C read as a key by the line editor to act as `SIGINT`.
+pub fn set_pending(sig: c_int) {
+    if let Some(pending) = PENDING.get(sig as usize) {
+        pending.store(true, Ordering::SeqCst);
+    }
+}
+
+/// the signals received since the last call
+pub fn take_pending() -> Vec<c_int> {
+    let mut signals = Vec::new();
+    for (sig, pending) in PENDING.iter().enumerate() {
+        if pending.swap(false, Ordering::SeqCst) {
+            signals.push(sig as c_int);
+        }
+    }
+    signals
+}
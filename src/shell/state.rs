@@ -0,0 +1,150 @@
+use libc::{c_int, pid_t};
+use std::{collections::BTreeMap, env, os::fd::RawFd};
+
+use super::{
+    completion::CompSpec,
+    history::with_history,
+    jobs::JobTable,
+    options::{self, ShellOptions},
+    path::Hashed,
+    signals,
+};
+use crate::interpreter::{command::finish_copies, nodes::Sequence};
+
+/// Everything the shell has to remember between two commands.
+#[derive(Default)]
+pub struct ShellState {
+    /// exit status of the most recently executed command, expanded by `$?`
+    pub last_status: i32,
+    /// process id of the most recent background command, expanded by `$!`
+    pub last_background: Option<pid_t>,
+    pub jobs: JobTable,
+    /// set when commands are read from a terminal
+    pub interactive: bool,
+    /// set for a login shell, which reads the profiles and `~/.0shell_logout`
+    pub login: bool,
+    /// commands run when a signal is received, an empty one ignores the signal,
+    /// `0` stands for the `EXIT` pseudo-signal
+    pub traps: BTreeMap<c_int, String>,
+    /// bodies of the functions defined with `name() { ... }`
+    pub functions: BTreeMap<String, Sequence>,
+    /// `$0`: the name the shell was started with, or the script it runs
+    pub name: String,
+    /// `$1`, `$2`... the arguments of the script or of the running function
+    pub positional: Vec<String>,
+    /// where `getopts` stopped: the `OPTIND` it set and the next option within that argument
+    pub getopts: (usize, usize),
+    /// completion specifications set by `complete`, by command name
+    pub completions: BTreeMap<String, CompSpec>,
+    /// options changed with `set`
+    pub options: ShellOptions,
+    /// arrays, such as `BASH_REMATCH`, whose elements are expanded by `${name[index]}`
+    pub arrays: BTreeMap<String, Vec<String>>,
+    /// executables looked up in `PATH`, forgotten when it's set
+    pub hashed: Hashed,
+    /// the processes copying between the descriptors `exec` redirected to several files
+    /// and these files
+    pub redirections: Vec<(RawFd, pid_t)>,
+    /// set once `exit` warned about stopped jobs, a second `exit` in a row leaves anyway
+    pub exit_warned: bool,
+}
+
+impl ShellState {
+    /// Forgets what a forked child must not act upon, such as the jobs of its parent.
+    ///
+    /// Trapped signals are reset to their default disposition, ignored ones stay ignored.
+    pub fn subshell(&mut self) {
+        self.jobs = JobTable::default();
+        self.jobs.pipefail = self.options.pipefail;
+        self.interactive = false;
+        // only the shell itself sources the logout file
+        self.login = false;
+        // the copies are children of the parent
+        self.redirections.clear();
+        with_history(|history| history.disable());
+        self.traps.retain(|_, action| action.is_empty());
+
+        let ignored: Vec<c_int> = self.traps.keys().copied().collect();
+        signals::reset_signals(&ignored);
+    }
+
+    /// Waits for the output `exec` redirected to be written out, before the shell exits.
+    pub fn finish_redirections(&mut self) {
+        finish_copies(&std::mem::take(&mut self.redirections));
+    }
+
+    /// Sets the variable `name` to `value`, or unsets it, variables are kept in the environment
+    /// so the commands run by the shell see them.
+    pub fn set_var(&mut self, name: &str, value: Option<&str>) {
+        self.arrays.remove(name);
+        if name == "PATH" {
+            self.hashed.clear();
+        }
+        // SAFETY: the shell has a single thread, redirections are copied by other processes
+        unsafe {
+            match value {
+                Some(value) => env::set_var(name, value),
+                None => env::remove_var(name),
+            }
+        }
+    }
+
+    /// The value of the variable `name`, or of an element of an array with `name[index]`,
+    /// `name[@]` joining all of them. An array alone stands for its first element.
+    pub fn var(&self, name: &str) -> Option<String> {
+        let Some((name, index)) = name.strip_suffix(']').and_then(|name| name.split_once('[')) else {
+            return match self.arrays.get(name) {
+                Some(array) => array.first().cloned(),
+                None => env::var(name).ok(),
+            };
+        };
+
+        match (self.arrays.get(name), index) {
+            (Some(array), "@" | "*") => Some(array.join(" ")),
+            (Some(array), index) => array.get(index.parse::<usize>().ok()?).cloned(),
+            (None, "0" | "@" | "*") => env::var(name).ok(),
+            (None, _) => None,
+        }
+    }
+
+    /// Replaces the variable `name` with an array, which isn't exported.
+    pub fn set_array(&mut self, name: &str, values: Vec<String>) {
+        self.set_var(name, None);
+        self.arrays.insert(name.to_string(), values);
+    }
+
+    /// the letters of the options set, as expanded by `$-`, `i` standing for an interactive shell
+    pub fn flags(&self) -> String {
+        let mut flags = self.options.flags();
+        if self.interactive {
+            flags.push('i');
+        }
+        flags
+    }
+
+    /// Sets or unsets the option `name`, returns `false` for an unknown option.
+    pub fn set_option(&mut self, name: &str, on: bool) -> bool {
+        if !self.options.set(name, on) {
+            return false;
+        }
+        self.jobs.pipefail = self.options.pipefail;
+        options::set_verbose(self.options.verbose);
+        true
+    }
+
+    /// Sets the action for `sig`, `None` restores the default one.
+    pub fn set_trap(&mut self, sig: c_int, action: Option<String>) {
+        if sig != 0 {
+            match action.as_deref() {
+                Some("") => signals::ignore(sig),
+                Some(_) => signals::catch(sig),
+                None => signals::restore(sig, self.interactive),
+            }
+        }
+
+        match action {
+            Some(action) => self.traps.insert(sig, action),
+            None => self.traps.remove(&sig),
+        };
+    }
+}
@@ -0,0 +1,99 @@
+/// Where backslash escape sequences are read, which decides how octal values are written.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Escapes {
+    /// the format of `printf`: octal values are `\nnn`
+    Format,
+    /// `echo`: octal values are `\0nnn`, `\c` ends the output
+    Echo,
+    /// the arguments of `printf %b`: octal values are `\0nnn` or `\nnn`, `\c` ends the output
+    Argument,
+}
+
+/// What an escape sequence stands for.
+pub enum Escape {
+    Bytes(Vec<u8>),
+    /// `\c`: nothing more is written
+    Stop,
+}
+
+/// Reads the escape sequence after a backslash, `rest` being the text following it.
+/// Returns what it stands for and the number of characters it takes,
+/// an unknown sequence stands for itself, backslash included.
+pub fn escape(rest: &[char], escapes: Escapes) -> (Escape, usize) {
+    let Some(&ch) = rest.first() else {
+        return (Escape::Bytes(b"\\".to_vec()), 0);
+    };
+
+    let byte = match ch {
+        '\\' => b'\\',
+        'a' => 0x07,
+        'b' => 0x08,
+        'e' | 'E' => 0x1b,
+        'f' => 0x0c,
+        'n' => b'\n',
+        'r' => b'\r',
+        't' => b'\t',
+        'v' => 0x0b,
+        '"' if escapes != Escapes::Echo => b'"',
+        '\'' if escapes != Escapes::Echo => b'\'',
+        'c' if escapes != Escapes::Format => return (Escape::Stop, 1),
+        '0' if escapes != Escapes::Format => {
+            let (value, len) = number(&rest[1..], 8, 3);
+            return (Escape::Bytes(vec![value as u8]), len + 1);
+        }
+        '0'..='7' if escapes != Escapes::Echo => {
+            let (value, len) = number(rest, 8, 3);
+            return (Escape::Bytes(vec![value as u8]), len);
+        }
+        'x' => match number(&rest[1..], 16, 2) {
+            (_, 0) => return (Escape::Bytes(b"\\x".to_vec()), 1),
+            (value, len) => return (Escape::Bytes(vec![value as u8]), len + 1),
+        },
+        'u' | 'U' => {
+            let max = if ch == 'u' { 4 } else { 8 };
+            return match number(&rest[1..], 16, max) {
+                (_, 0) => (Escape::Bytes(format!("\\{ch}").into_bytes()), 1),
+                (value, len) => {
+                    let ch = char::from_u32(value).unwrap_or(char::REPLACEMENT_CHARACTER);
+                    (Escape::Bytes(ch.to_string().into_bytes()), len + 1)
+                }
+            };
+        }
+        _ => return (Escape::Bytes(format!("\\{ch}").into_bytes()), 1),
+    };
+    (Escape::Bytes(vec![byte]), 1)
+}
+
+/// Expands the escape sequences of `text`, returns whether `\c` ended it.
+pub fn expand(text: &str, escapes: Escapes) -> (Vec<u8>, bool) {
+    let chars: Vec<char> = text.chars().collect();
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut i = 0;
+    while i < chars.len() {
+        if chars[i] != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend(chars[i].encode_utf8(&mut buf).as_bytes());
+            i += 1;
+            continue;
+        }
+
+        let (escape, len) = escape(&chars[i + 1..], escapes);
+        match escape {
+            Escape::Bytes(escaped) => bytes.extend(escaped),
+            Escape::Stop => return (bytes, true),
+        }
+        i += len + 1;
+    }
+    (bytes, false)
+}
+
+/// the value of the digits in `radix` starting `chars`, at most `max` of them, and their number
+fn number(chars: &[char], radix: u32, max: usize) -> (u32, usize) {
+    let mut value = 0;
+    let mut len = 0;
+    for digit in chars.iter().take(max).map_while(|ch| ch.to_digit(radix)) {
+        value = value * radix + digit;
+        len += 1;
+    }
+    (value, len)
+}
@@ -0,0 +1,1000 @@
+use std::{
+    cell::RefCell,
+    collections::{BinaryHeap, HashMap},
+    fmt,
+    fs::{self, File},
+    os::unix::fs::{FileExt, MetadataExt},
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::utils::inflate;
+
+/// most commits looked at to count the commits apart from the upstream, the counts being
+/// left out past it
+const WALK_LIMIT: usize = 10_000;
+/// most commits kept read, the cache being emptied past it
+const CACHED_COMMITS: usize = 100_000;
+
+/// the side of the history a commit can be reached from, when counting commits apart
+const LOCAL: u8 = 1;
+const UPSTREAM: u8 = 2;
+
+thread_local! {
+    /// whether files whose times differ from the index changed, by path, along with
+    /// the file and index data the answer holds for, so a file is hashed once per change
+    static HASHED: RefCell<HashMap<PathBuf, (Stat, bool)>> = RefCell::new(HashMap::new());
+    /// the commits read, by hash, which never change
+    static COMMITS: RefCell<HashMap<Vec<u8>, Rc<Commit>>> = RefCell::new(HashMap::new());
+}
+
+/// what a file and its index entry were when the file was hashed
+#[derive(PartialEq)]
+struct Stat {
+    size: u64,
+    mtime: i64,
+    mtime_nsec: i64,
+    ino: u64,
+    hash: Vec<u8>,
+}
+
+/// The state of a git repository shown in prompts, read from its files.
+pub struct GitStatus {
+    /// the branch checked out, or a tag or an abbreviated commit on a detached HEAD
+    pub head: String,
+    pub detached: bool,
+    /// tracked files of the working tree differ from the index
+    pub dirty: bool,
+    /// commits not pushed to the upstream branch, 0 when the histories can't be read
+    /// or are too far apart to be counted quickly
+    pub ahead: usize,
+    /// commits of the upstream branch not pulled
+    pub behind: usize,
+    /// the merge, rebase, ... in progress
+    pub operation: Option<&'static str>,
+}
+
+/// a repository, `common` holds the refs shared by all its worktrees
+struct Repo {
+    git_dir: PathBuf,
+    common: PathBuf,
+    work_tree: PathBuf,
+}
+
+/// files marking the operations in progress, with the name they are shown with
+const OPERATIONS: &[(&str, &str)] = &[
+    ("rebase-merge", "REBASE"),
+    ("rebase-apply", "REBASE"),
+    ("MERGE_HEAD", "MERGING"),
+    ("CHERRY_PICK_HEAD", "CHERRY-PICKING"),
+    ("REVERT_HEAD", "REVERTING"),
+    ("BISECT_LOG", "BISECTING"),
+];
+
+impl fmt::Display for GitStatus {
+    /// `main *↑1↓2|MERGING`, a detached HEAD being shown in parentheses
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.detached {
+            true => write!(f, "({})", self.head)?,
+            false => write!(f, "{}", self.head)?,
+        }
+        if self.dirty {
+            write!(f, " *")?;
+        }
+        if self.ahead > 0 || self.behind > 0 {
+            write!(f, " ")?;
+        }
+        if self.ahead > 0 {
+            write!(f, "↑{}", self.ahead)?;
+        }
+        if self.behind > 0 {
+            write!(f, "↓{}", self.behind)?;
+        }
+        if let Some(operation) = self.operation {
+            write!(f, "|{operation}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the state of the repository containing `dir`, `None` outside of a repository.
+pub fn status(dir: &Path) -> Option<GitStatus> {
+    let repo = Repo::find(dir)?;
+    let head = fs::read_to_string(repo.git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+
+    let operation = OPERATIONS
+        .iter()
+        .find(|(file, _)| repo.git_dir.join(file).exists())
+        .map(|(_, name)| *name);
+
+    let (name, detached, branch) = match head.strip_prefix("ref: ") {
+        Some(target) => {
+            let name = target.strip_prefix("refs/heads/").unwrap_or(target);
+            (name.to_string(), false, Some(target.to_string()))
+        }
+        None => {
+            // a rebase detaches HEAD from the branch it rewrites
+            let rebased = ["rebase-merge", "rebase-apply"]
+                .iter()
+                .find_map(|dir| fs::read_to_string(repo.git_dir.join(dir).join("head-name")).ok());
+            match rebased {
+                Some(target) => {
+                    let target = target.trim();
+                    (target.strip_prefix("refs/heads/").unwrap_or(target).to_string(), false, None)
+                }
+                None => (repo.describe(head), true, None),
+            }
+        }
+    };
+
+    let (ahead, behind) = branch.map(|branch| repo.divergence(&branch)).unwrap_or_default();
+
+    Some(GitStatus {
+        head: name,
+        detached,
+        dirty: repo.is_dirty(),
+        ahead,
+        behind,
+        operation,
+    })
+}
+
+impl Repo {
+    /// Walks up from `dir` to the first directory holding a `.git` directory, or a `.git` file
+    /// pointing to one as worktrees and submodules have.
+    fn find(dir: &Path) -> Option<Self> {
+        for work_tree in dir.ancestors() {
+            let dot_git = work_tree.join(".git");
+            let git_dir = if dot_git.is_dir() {
+                dot_git
+            } else if let Ok(content) = fs::read_to_string(&dot_git) {
+                let target = content.trim().strip_prefix("gitdir: ")?;
+                work_tree.join(target)
+            } else {
+                continue;
+            };
+
+            let common = match fs::read_to_string(git_dir.join("commondir")) {
+                Ok(common) => git_dir.join(common.trim()),
+                Err(_) => git_dir.clone(),
+            };
+            return Some(Self { git_dir, common, work_tree: work_tree.to_path_buf() });
+        }
+        None
+    }
+
+    /// the commit `name` points to, following symbolic refs
+    fn resolve(&self, name: &str) -> Option<String> {
+        let mut name = name.to_string();
+        // symbolic refs pointing to each other in a loop are given up on
+        for _ in 0..5 {
+            let loose = fs::read_to_string(self.git_dir.join(&name))
+                .or_else(|_| fs::read_to_string(self.common.join(&name)))
+                .ok();
+            let value = match loose {
+                Some(value) => value.trim().to_string(),
+                None => return self.packed_refs().into_iter().find(|(_, r)| *r == name).map(|(hash, _)| hash),
+            };
+            match value.strip_prefix("ref: ") {
+                Some(target) => name = target.to_string(),
+                None => return Some(value),
+            }
+        }
+        None
+    }
+
+    /// the hashes and names listed in `packed-refs`
+    fn packed_refs(&self) -> Vec<(String, String)> {
+        let content = fs::read_to_string(self.common.join("packed-refs")).unwrap_or_default();
+        content
+            .lines()
+            .filter(|line| !line.starts_with('#') && !line.starts_with('^'))
+            .filter_map(|line| line.split_once(' '))
+            .map(|(hash, name)| (hash.to_string(), name.to_string()))
+            .collect()
+    }
+
+    /// a tag pointing to `hash`, otherwise its abbreviation
+    fn describe(&self, hash: &str) -> String {
+        let loose = fs::read_dir(self.common.join("refs/tags"))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .find(|tag| self.resolve(&format!("refs/tags/{tag}")).as_deref() == Some(hash));
+
+        let packed = || {
+            self.packed_refs()
+                .into_iter()
+                .find(|(target, name)| target == hash && name.starts_with("refs/tags/"))
+                .map(|(_, name)| name["refs/tags/".len()..].to_string())
+        };
+
+        loose.or_else(packed).unwrap_or_else(|| format!("{}...", &hash[..hash.len().min(7)]))
+    }
+
+    /// The upstream of `branch` from the config, `branch.<name>.remote` and `branch.<name>.merge`.
+    fn upstream(&self, branch: &str) -> Option<String> {
+        let name = branch.strip_prefix("refs/heads/")?;
+        let section = format!("branch \"{name}\"");
+        let remote = self.config(&section, "remote")?;
+        let merge = self.config(&section, "merge")?;
+        let merge = merge.strip_prefix("refs/heads/").unwrap_or(&merge);
+
+        match remote.as_str() {
+            // a branch tracking another local branch
+            "." => Some(format!("refs/heads/{merge}")),
+            _ => Some(format!("refs/remotes/{remote}/{merge}")),
+        }
+    }
+
+    /// the value of `key` in `[section]` of the config of the repository
+    fn config(&self, section: &str, key: &str) -> Option<String> {
+        let content = fs::read_to_string(self.common.join("config")).ok()?;
+        let mut in_section = false;
+
+        for line in content.lines().map(str::trim) {
+            if let Some(header) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+                in_section = header.trim() == section;
+                continue;
+            }
+            if !in_section {
+                continue;
+            }
+            if let Some((name, value)) = line.split_once('=')
+                && name.trim().eq_ignore_ascii_case(key)
+            {
+                return Some(value.trim().trim_matches('"').to_string());
+            }
+        }
+        None
+    }
+
+    /// The commits `branch` and its upstream each have that the other doesn't. The histories are
+    /// walked from their newest commits on, until the commits left are reachable from both.
+    fn divergence(&self, branch: &str) -> (usize, usize) {
+        let Some(upstream) = self.upstream(branch) else {
+            return (0, 0);
+        };
+        let (Some(local), Some(remote)) = (self.resolve(branch), self.resolve(&upstream)) else {
+            return (0, 0);
+        };
+        if local == remote {
+            return (0, 0);
+        }
+        let objects = Objects::open(&self.common, self.hash_len());
+        count_apart(&objects, &local, &remote).unwrap_or_default()
+    }
+
+    /// the length of the hashes of the repository, in bytes
+    fn hash_len(&self) -> usize {
+        match self.config("extensions", "objectformat").as_deref() {
+            Some("sha256") => 32,
+            _ => 20,
+        }
+    }
+
+    /// Whether a tracked file differs from the index: it's missing or its size changed, or its
+    /// modification time changed and so did its content.
+    fn is_dirty(&self) -> bool {
+        let Ok(index) = fs::read(self.git_dir.join("index")) else {
+            return false;
+        };
+        let index_mtime = fs::metadata(self.git_dir.join("index")).map(|meta| meta.mtime()).unwrap_or(0);
+        let Some(entries) = parse_index(&index, self.hash_len()) else {
+            return false;
+        };
+        entries.iter().any(|entry| self.entry_changed(entry, index_mtime))
+    }
+
+    fn entry_changed(&self, entry: &IndexEntry, index_mtime: i64) -> bool {
+        // gitlinks are the commits of submodules, and other stages are unresolved conflicts
+        if entry.mode & 0o170000 == 0o160000 || entry.skip {
+            return false;
+        }
+        if entry.stage != 0 {
+            return true;
+        }
+
+        let path = self.work_tree.join(&entry.path);
+        let Ok(meta) = fs::symlink_metadata(&path) else {
+            return true;
+        };
+        if meta.size() as u32 != entry.size {
+            return true;
+        }
+
+        // a file changed in the second the index was written may have kept its time
+        let racy = meta.mtime() >= index_mtime;
+        let same_time = meta.mtime() as u32 == entry.mtime && meta.mtime_nsec() as u32 == entry.mtime_nsec;
+        if same_time && !racy {
+            return false;
+        }
+
+        // only SHA-1 is computed, a SHA-256 repository relies on the times
+        if entry.hash.len() != 20 {
+            return !same_time;
+        }
+
+        let stat = Stat {
+            size: meta.size(),
+            mtime: meta.mtime(),
+            mtime_nsec: meta.mtime_nsec(),
+            ino: meta.ino(),
+            hash: entry.hash.clone(),
+        };
+        let known = HASHED.with_borrow(|hashed| {
+            hashed.get(&path).filter(|(known, _)| *known == stat).map(|(_, changed)| *changed)
+        });
+        if let Some(changed) = known {
+            return changed;
+        }
+
+        let content = if meta.file_type().is_symlink() {
+            fs::read_link(&path).map(|target| target.into_os_string().into_encoded_bytes())
+        } else {
+            fs::read(&path)
+        };
+        let changed = match content {
+            Ok(content) => blob_hash(&content) != entry.hash.as_slice(),
+            Err(_) => true,
+        };
+        // a file may change again without its time changing, as long as that time is now
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |now| now.as_secs() as i64);
+        if stat.mtime < now {
+            HASHED.with_borrow_mut(|hashed| hashed.insert(path, (stat, changed)));
+        }
+        changed
+    }
+}
+
+/// the commit time and parents of a commit
+struct Commit {
+    time: i64,
+    parents: Vec<Vec<u8>>,
+}
+
+/// Counts the commits reachable from `local` but not from `remote`, and the other way around,
+/// as `git rev-list --left-right --count local...remote` does. The newest commit is looked at
+/// first, a commit missing from a shallow clone is taken as a root.
+fn count_apart(objects: &Objects, local: &str, remote: &str) -> Option<(usize, usize)> {
+    let mut sides: HashMap<Vec<u8>, u8> = HashMap::new();
+    let mut queue = BinaryHeap::new();
+    for (hash, side) in [(unhex(local)?, LOCAL), (unhex(remote)?, UPSTREAM)] {
+        let commit = objects.commit(&hash)?;
+        *sides.entry(hash.clone()).or_default() |= side;
+        queue.push((commit.time, hash));
+    }
+
+    let mut walked = 0;
+    while queue.iter().any(|(_, hash)| sides[hash] != LOCAL | UPSTREAM) {
+        let Some((_, hash)) = queue.pop() else {
+            break;
+        };
+        walked += 1;
+        if walked > WALK_LIMIT {
+            return None;
+        }
+
+        let side = sides[&hash];
+        let Some(commit) = objects.commit(&hash) else {
+            continue;
+        };
+        for parent in &commit.parents {
+            let known = sides.entry(parent.clone()).or_default();
+            if *known | side == *known {
+                continue;
+            }
+            *known |= side;
+            if let Some(parent_commit) = objects.commit(parent) {
+                queue.push((parent_commit.time, parent.clone()));
+            }
+        }
+    }
+
+    let count = |side| sides.values().filter(|&&known| known == side).count();
+    Some((count(LOCAL), count(UPSTREAM)))
+}
+
+/// The objects of a repository: loose ones, compressed in a file each, and those of the packs.
+/// https://git-scm.com/docs/gitformat-pack
+struct Objects {
+    /// the object directories, the one of the repository and its alternates
+    dirs: Vec<PathBuf>,
+    /// the indexes of the packs, with the packs
+    packs: Vec<(File, File)>,
+    hash_len: usize,
+}
+
+/// the type of commits in object headers and packs
+const COMMIT: u8 = 1;
+/// packed objects stored as a delta from an object at an offset before them, or with a hash
+const OFS_DELTA: u8 = 6;
+const REF_DELTA: u8 = 7;
+/// longest chain of deltas followed
+const DELTA_DEPTH: usize = 1000;
+
+impl Objects {
+    fn open(common: &Path, hash_len: usize) -> Self {
+        let objects = common.join("objects");
+        let alternates = fs::read_to_string(objects.join("info/alternates")).unwrap_or_default();
+        let mut dirs = vec![objects.clone()];
+        dirs.extend(
+            alternates
+                .lines()
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(|line| objects.join(line)),
+        );
+
+        let mut packs = Vec::new();
+        for dir in &dirs {
+            let entries = fs::read_dir(dir.join("pack")).into_iter().flatten().flatten();
+            for entry in entries {
+                let path = entry.path();
+                if path.extension().is_some_and(|ext| ext == "idx")
+                    && let (Ok(index), Ok(pack)) = (File::open(&path), File::open(path.with_extension("pack")))
+                {
+                    packs.push((index, pack));
+                }
+            }
+        }
+        Self { dirs, packs, hash_len }
+    }
+
+    /// the commit `hash`, read once
+    fn commit(&self, hash: &[u8]) -> Option<Rc<Commit>> {
+        if let Some(commit) = COMMITS.with_borrow(|commits| commits.get(hash).cloned()) {
+            return Some(commit);
+        }
+
+        let (kind, data) = self.object(hash, 0)?;
+        if kind != COMMIT {
+            return None;
+        }
+        let commit = Rc::new(parse_commit(&data)?);
+        COMMITS.with_borrow_mut(|commits| {
+            if commits.len() >= CACHED_COMMITS {
+                commits.clear();
+            }
+            commits.insert(hash.to_vec(), commit.clone());
+        });
+        Some(commit)
+    }
+
+    /// the type and content of the object `hash`
+    fn object(&self, hash: &[u8], depth: usize) -> Option<(u8, Vec<u8>)> {
+        let name = hex(hash);
+        for dir in &self.dirs {
+            if let Ok(data) = fs::read(dir.join(&name[..2]).join(&name[2..])) {
+                return loose_object(&data);
+            }
+        }
+
+        self.packs.iter().find_map(|(index, pack)| {
+            let offset = pack_offset(index, hash, self.hash_len)?;
+            self.packed(pack, offset, depth)
+        })
+    }
+
+    /// the type and content of the object at `offset` in `pack`, rebuilt from its base for a delta
+    fn packed(&self, pack: &File, offset: u64, depth: usize) -> Option<(u8, Vec<u8>)> {
+        if depth > DELTA_DEPTH {
+            return None;
+        }
+
+        // the type and the size, 4 bits of it then 7 in each following byte
+        let header = read_at(pack, offset, 32 + self.hash_len);
+        let mut pos = 0;
+        let mut byte = *header.first()?;
+        let kind = (byte >> 4) & 7;
+        let mut size = usize::from(byte & 0x0f);
+        let mut shift = 4;
+        while byte & 0x80 != 0 {
+            pos += 1;
+            byte = *header.get(pos)?;
+            size |= usize::from(byte & 0x7f).checked_shl(shift)?;
+            shift += 7;
+        }
+        pos += 1;
+
+        let (base, data_offset) = match kind {
+            OFS_DELTA => {
+                let (distance, len) = varint(header.get(pos..)?)?;
+                let base = self.packed(pack, offset.checked_sub(distance as u64)?, depth + 1)?;
+                (base, offset + (pos + len) as u64)
+            }
+            REF_DELTA => {
+                let base = self.object(header.get(pos..pos + self.hash_len)?, depth + 1)?;
+                (base, offset + (pos + self.hash_len) as u64)
+            }
+            _ => return Some((kind, inflate_at(pack, offset + pos as u64, size)?)),
+        };
+
+        let delta = inflate_at(pack, data_offset, size)?;
+        let (kind, base) = base;
+        Some((kind, apply_delta(&base, &delta)?))
+    }
+}
+
+/// the type and content of a loose object, `type size\0content` compressed
+fn loose_object(data: &[u8]) -> Option<(u8, Vec<u8>)> {
+    let data = inflate::zlib(data)?;
+    let end = data.iter().position(|&b| b == 0)?;
+    let kind = match data[..end].split(|&b| b == b' ').next()? {
+        b"commit" => COMMIT,
+        b"tree" => 2,
+        b"blob" => 3,
+        b"tag" => 4,
+        _ => return None,
+    };
+    Some((kind, data[end + 1..].to_vec()))
+}
+
+/// the parents and the commit time of a commit, in its headers
+fn parse_commit(data: &[u8]) -> Option<Commit> {
+    let mut commit = Commit { time: 0, parents: Vec::new() };
+    for line in data.split(|&b| b == b'\n').take_while(|line| !line.is_empty()) {
+        let line = std::str::from_utf8(line).ok()?;
+        if let Some(parent) = line.strip_prefix("parent ") {
+            commit.parents.push(unhex(parent)?);
+        } else if let Some(committer) = line.strip_prefix("committer ") {
+            // `name <email> time zone`
+            commit.time = committer.rsplit(' ').nth(1)?.parse().ok()?;
+        }
+    }
+    Some(commit)
+}
+
+/// The offset of the object `hash` in the pack of `index`, by dichotomy over its sorted hashes.
+/// https://git-scm.com/docs/gitformat-pack#_version_2_pack_idx_files_support_packs_larger_than_4_gib_and
+fn pack_offset(index: &File, hash: &[u8], hash_len: usize) -> Option<u64> {
+    let u32_at = |pos: u64| -> Option<u32> { Some(u32::from_be_bytes(read_at(index, pos, 4).try_into().ok()?)) };
+
+    if read_at(index, 0, 8) != [0xff, b't', b'O', b'c', 0, 0, 0, 2] {
+        return None;
+    }
+    // the number of hashes up to each first byte
+    let fanout = |byte: usize| u32_at(8 + 4 * byte as u64);
+    let first = usize::from(*hash.first()?);
+    let (mut low, mut high) = match first {
+        0 => (0, fanout(0)?),
+        _ => (fanout(first - 1)?, fanout(first)?),
+    };
+    let total = u64::from(fanout(255)?);
+    let hashes = 8 + 4 * 256;
+
+    while low < high {
+        let middle = (low + high) / 2;
+        let found = read_at(index, hashes + u64::from(middle) * hash_len as u64, hash_len);
+        match found.as_slice().cmp(hash) {
+            std::cmp::Ordering::Less => low = middle + 1,
+            std::cmp::Ordering::Greater => high = middle,
+            std::cmp::Ordering::Equal => {
+                // CRCs then offsets follow the hashes, those of 31 bits or more being in a table after
+                let offsets = hashes + total * (hash_len as u64 + 4);
+                let offset = u32_at(offsets + 4 * u64::from(middle))?;
+                if offset & 0x8000_0000 == 0 {
+                    return Some(u64::from(offset));
+                }
+                let large = offsets + total * 4 + 8 * u64::from(offset & 0x7fff_ffff);
+                return Some(u64::from_be_bytes(read_at(index, large, 8).try_into().ok()?));
+            }
+        }
+    }
+    None
+}
+
+/// up to `len` bytes of `file` from `pos`, fewer at its end
+fn read_at(file: &File, pos: u64, len: usize) -> Vec<u8> {
+    let mut buf = vec![0; len];
+    let mut read = 0;
+    while read < len {
+        match file.read_at(&mut buf[read..], pos + read as u64) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => read += n,
+        }
+    }
+    buf.truncate(read);
+    buf
+}
+
+/// decompresses the data at `pos` in a pack, of `size` bytes once decompressed
+fn inflate_at(pack: &File, pos: u64, size: usize) -> Option<Vec<u8>> {
+    // data that doesn't compress grows by a few bytes per block
+    let data = read_at(pack, pos, size + size / 64 + 64);
+    inflate::zlib(&data).filter(|data| data.len() == size)
+}
+
+/// Rebuilds an object from its `base` and a delta: the sizes of both, then instructions
+/// copying a part of the base or inserting the bytes that follow them.
+fn apply_delta(base: &[u8], delta: &[u8]) -> Option<Vec<u8>> {
+    let mut pos = 0;
+    let mut size = || {
+        let mut value = 0usize;
+        let mut shift = 0;
+        loop {
+            let byte = *delta.get(pos)?;
+            pos += 1;
+            value |= usize::from(byte & 0x7f).checked_shl(shift)?;
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Some(value);
+            }
+        }
+    };
+    let base_size = size()?;
+    let target_size = size()?;
+    if base_size != base.len() {
+        return None;
+    }
+
+    let mut target = Vec::with_capacity(target_size);
+    while let Some(&op) = delta.get(pos) {
+        pos += 1;
+        if op & 0x80 != 0 {
+            // the bits of the instruction tell which bytes of the offset and size follow
+            let mut field = |bits: u8, count: u8| {
+                let mut value = 0usize;
+                for i in 0..count {
+                    if bits & (1 << i) != 0 {
+                        value |= usize::from(*delta.get(pos)?) << (8 * i);
+                        pos += 1;
+                    }
+                }
+                Some(value)
+            };
+            let offset = field(op, 4)?;
+            let len = match field(op >> 4, 3)? {
+                0 => 0x10000,
+                len => len,
+            };
+            target.extend_from_slice(base.get(offset..offset.checked_add(len)?)?);
+        } else if op != 0 {
+            let len = usize::from(op);
+            target.extend_from_slice(delta.get(pos..pos + len)?);
+            pos += len;
+        } else {
+            return None;
+        }
+    }
+    (target.len() == target_size).then_some(target)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..text.len()).step_by(2).map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok()).collect()
+}
+
+struct IndexEntry {
+    mtime: u32,
+    mtime_nsec: u32,
+    mode: u32,
+    size: u32,
+    hash: Vec<u8>,
+    /// 0 for a resolved file, the side of a conflict otherwise
+    stage: u16,
+    /// marked `assume-unchanged` or `skip-worktree`
+    skip: bool,
+    path: String,
+}
+
+/// Parses the entries of an index file, versions 2 to 4.
+/// https://git-scm.com/docs/index-format
+fn parse_index(data: &[u8], hash_len: usize) -> Option<Vec<IndexEntry>> {
+    let u32_at = |pos: usize| -> Option<u32> { Some(u32::from_be_bytes(data.get(pos..pos + 4)?.try_into().ok()?)) };
+    let u16_at = |pos: usize| -> Option<u16> { Some(u16::from_be_bytes(data.get(pos..pos + 2)?.try_into().ok()?)) };
+
+    if data.get(..4)? != b"DIRC" {
+        return None;
+    }
+    let version = u32_at(4)?;
+    if !(2..=4).contains(&version) {
+        return None;
+    }
+    let count = u32_at(8)? as usize;
+
+    let mut entries = Vec::with_capacity(count);
+    let mut pos = 12;
+    let mut previous = String::new();
+
+    for _ in 0..count {
+        let start = pos;
+        let mode = u32_at(pos + 24)?;
+        let flags = u16_at(pos + 40 + hash_len)?;
+        let hash = data.get(pos + 40..pos + 40 + hash_len)?.to_vec();
+        let mut skip = flags & 0x8000 != 0;
+        pos += 42 + hash_len;
+
+        if version >= 3 && flags & 0x4000 != 0 {
+            skip |= u16_at(pos)? & 0x4000 != 0;
+            pos += 2;
+        }
+
+        let path = if version == 4 {
+            // the path is the end of the previous one replaced by a new suffix
+            let (strip, len) = varint(data.get(pos..)?)?;
+            pos += len;
+            let end = pos + data.get(pos..)?.iter().position(|&b| b == 0)?;
+            let suffix = String::from_utf8_lossy(&data[pos..end]);
+            pos = end + 1;
+            let kept = previous.len().checked_sub(strip)?;
+            format!("{}{suffix}", previous.get(..kept)?)
+        } else {
+            let end = pos + data.get(pos..)?.iter().position(|&b| b == 0)?;
+            let path = String::from_utf8_lossy(&data[pos..end]).into_owned();
+            // entries are padded with 1 to 8 NULs to a multiple of 8 bytes
+            pos = start + (end - start + 8) / 8 * 8;
+            path
+        };
+        previous = path.clone();
+
+        entries.push(IndexEntry {
+            mtime: u32_at(start + 8)?,
+            mtime_nsec: u32_at(start + 12)?,
+            mode,
+            size: u32_at(start + 36)?,
+            hash,
+            stage: (flags >> 12) & 0x3,
+            skip,
+            path,
+        });
+    }
+
+    Some(entries)
+}
+
+/// the variable length integer of the index, returns its value and its length in bytes
+fn varint(data: &[u8]) -> Option<(usize, usize)> {
+    let mut bytes = data.iter();
+    let mut byte = *bytes.next()?;
+    let mut value = usize::from(byte & 0x7f);
+    let mut len = 1;
+    while byte & 0x80 != 0 {
+        byte = *bytes.next()?;
+        value = ((value + 1) << 7) | usize::from(byte & 0x7f);
+        len += 1;
+    }
+    Some((value, len))
+}
+
+/// the SHA-1 git names a file with `content` by
+fn blob_hash(content: &[u8]) -> [u8; 20] {
+    let mut data = format!("blob {}\0", content.len()).into_bytes();
+    data.extend_from_slice(content);
+    sha1(&data)
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&(data.len() as u64 * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..20 => ((b & c) | (!b & d), 0x5A827999),
+                20..40 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..60 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (chunk, word) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// an index entry for `path` with only the fields that are parsed set, with `v4_strip`
+    /// the path is the suffix replacing that many bytes of the previous one, as version 4 has
+    fn entry(path: &str, mtime: u32, size: u32, flags: u16, v4_strip: Option<u8>) -> Vec<u8> {
+        let mut data = vec![0; 8];
+        data.extend(mtime.to_be_bytes());
+        data.extend(7u32.to_be_bytes());
+        data.extend([0; 8]);
+        data.extend(0o100644u32.to_be_bytes());
+        data.extend([0; 8]);
+        data.extend(size.to_be_bytes());
+        data.extend([0xab; 20]);
+        data.extend((flags | path.len() as u16).to_be_bytes());
+        match v4_strip {
+            Some(strip) => {
+                data.push(strip);
+                data.extend(path.as_bytes());
+                data.push(0);
+            }
+            None => {
+                data.extend(path.as_bytes());
+                let len = data.len();
+                data.resize((len + 8) / 8 * 8, 0);
+            }
+        }
+        data
+    }
+
+    fn index(version: u32, entries: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = b"DIRC".to_vec();
+        data.extend(version.to_be_bytes());
+        data.extend((entries.len() as u32).to_be_bytes());
+        entries.iter().for_each(|entry| data.extend(entry));
+        data
+    }
+
+    #[test]
+    fn sha1_vectors() {
+        assert_eq!(hex(&sha1(b"")), "da39a3ee5e6b4b0d3255bfef95601890afd80709");
+        assert_eq!(hex(&sha1(b"abc")), "a9993e364706816aba3e25717850c26c9cd0d89d");
+        assert_eq!(
+            hex(&sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1"
+        );
+        assert_eq!(hex(&sha1(&[b'a'; 1_000_000])), "34aa973cd4c4daa4f61eeb2bdbad27316534016f");
+    }
+
+    #[test]
+    fn blob_hash_matches_git() {
+        // `printf 'hello\n' | git hash-object --stdin`
+        assert_eq!(hex(&blob_hash(b"hello\n")), "ce013625030ba8dba906f756967f9e9ca394464a");
+        assert_eq!(hex(&blob_hash(b"")), "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    }
+
+    #[test]
+    fn index_v2() {
+        let data = index(2, &[entry("Cargo.toml", 100, 42, 0, None), entry("src/main.rs", 200, 7, 0x2000, None)]);
+        let entries = parse_index(&data, 20).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].path, "Cargo.toml");
+        assert_eq!((entries[0].mtime, entries[0].mtime_nsec, entries[0].size), (100, 7, 42));
+        assert_eq!(entries[0].mode, 0o100644);
+        assert_eq!(entries[0].hash, vec![0xab; 20]);
+        assert_eq!(entries[1].path, "src/main.rs");
+        assert_eq!(entries[1].stage, 2);
+        assert!(!entries[1].skip);
+    }
+
+    #[test]
+    fn index_v4_paths() {
+        let data = index(
+            4,
+            &[entry("src/lib.rs", 1, 1, 0x8000, Some(0)), entry("main.rs", 2, 2, 0, Some(6))],
+        );
+        let entries = parse_index(&data, 20).unwrap();
+        assert_eq!(entries[0].path, "src/lib.rs");
+        assert!(entries[0].skip);
+        assert_eq!(entries[1].path, "src/main.rs");
+    }
+
+    #[test]
+    fn index_rejected() {
+        assert!(parse_index(b"DIRX\0\0\0\x02\0\0\0\0", 20).is_none());
+        assert!(parse_index(&index(5, &[]), 20).is_none());
+        // an entry cut short
+        let mut data = index(2, &[entry("a", 0, 0, 0, None)]);
+        data.truncate(40);
+        assert!(parse_index(&data, 20).is_none());
+    }
+
+    #[test]
+    fn delta_copies_and_inserts() {
+        let base = b"the quick brown fox";
+        // sizes 19 and 13, copy 4 bytes from 0, insert "red ", copy 3 bytes from 16, then 2 from 4
+        let delta = [19, 13, 0x91, 0, 4, 4, b'r', b'e', b'd', b' ', 0x91, 16, 3, 0x91, 4, 2];
+        assert_eq!(apply_delta(base, &delta).unwrap(), b"the red foxqu");
+        // the base isn't the one the delta was made from
+        assert!(apply_delta(b"short", &delta).is_none());
+        // copies past the end of the base
+        assert!(apply_delta(base, &[19, 4, 0x91, 18, 4]).is_none());
+    }
+
+    #[test]
+    fn commit_headers() {
+        let data = b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n\
+parent 0102030405060708090a0b0c0d0e0f1011121314\n\
+parent 1112131415161718191a1b1c1d1e1f2021222324\n\
+author A U Thor <a@example.com> 1700000000 +0100\n\
+committer C O Mitter <c@example.com> 1700000100 -0500\n\n\
+parent not a header\n";
+        let commit = parse_commit(data).unwrap();
+        assert_eq!(commit.time, 1_700_000_100);
+        assert_eq!(commit.parents.len(), 2);
+        assert_eq!(hex(&commit.parents[1]), "1112131415161718191a1b1c1d1e1f2021222324");
+    }
+
+    /// runs git in `dir` for a test, `false` when it isn't installed
+    fn run_git(dir: &Path, args: &[&str]) -> bool {
+        std::process::Command::new("git")
+            .args(args)
+            .current_dir(dir)
+            .env("GIT_CONFIG_GLOBAL", "/dev/null")
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_AUTHOR_NAME", "a")
+            .env("GIT_AUTHOR_EMAIL", "a@example.com")
+            .env("GIT_COMMITTER_NAME", "a")
+            .env("GIT_COMMITTER_EMAIL", "a@example.com")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    }
+
+    #[test]
+    fn commits_apart_loose_and_packed() {
+        let dir = std::env::temp_dir().join(format!("0-shell-divergence-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        if !run_git(&dir, &["init", "-q", "-b", "main"]) {
+            return;
+        }
+
+        let commit = |n: usize| {
+            fs::write(dir.join(n.to_string()), "line\n".repeat(50 + n)).unwrap();
+            let date = format!("@{} +0000", 1_700_000_000 + n);
+            assert!(run_git(&dir, &["add", "."]));
+            assert!(run_git(&dir, &["commit", "-q", "-m", &format!("commit {n}"), "--date", &date]));
+        };
+        (0..3).for_each(commit);
+        assert!(run_git(&dir, &["checkout", "-q", "-b", "topic"]));
+        assert!(run_git(&dir, &["config", "branch.topic.remote", "."]));
+        assert!(run_git(&dir, &["config", "branch.topic.merge", "refs/heads/main"]));
+        (3..5).for_each(commit);
+        assert!(run_git(&dir, &["checkout", "-q", "main"]));
+        (5..8).for_each(commit);
+        assert!(run_git(&dir, &["merge", "-q", "-m", "merge", "topic~1"]));
+        assert!(run_git(&dir, &["checkout", "-q", "topic"]));
+
+        let apart = || {
+            COMMITS.with_borrow_mut(HashMap::clear);
+            let status = status(&dir).unwrap();
+            (status.ahead, status.behind)
+        };
+        assert_eq!(apart(), (1, 4));
+        assert!(run_git(&dir, &["repack", "-q", "-a", "-d", "-f", "--depth=50", "--window=50"]));
+        assert!(run_git(&dir, &["prune-packed"]));
+        assert_eq!(apart(), (1, 4));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn varint_offsets() {
+        assert_eq!(varint(&[0x05]), Some((5, 1)));
+        assert_eq!(varint(&[0x80, 0x00]), Some((128, 2)));
+        assert_eq!(varint(&[0x81, 0x01]), Some((257, 2)));
+        assert_eq!(varint(&[0x80]), None);
+    }
+}
@@ -0,0 +1,248 @@
+/// base lengths of the length codes 257 to 285, and their number of extra bits
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0];
+/// base distances of the distance codes, and their number of extra bits
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+    8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+/// the order the lengths of the code length codes come in
+const CODE_LENGTH_ORDER: [usize; 19] = [16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15];
+
+/// Decompresses zlib data, as git stores its objects, `None` when it's corrupt or cut short.
+/// Anything following the compressed stream is ignored.
+/// https://www.rfc-editor.org/rfc/rfc1950
+pub fn zlib(data: &[u8]) -> Option<Vec<u8>> {
+    let [cmf, flg, ..] = *data else {
+        return None;
+    };
+    // deflate only, without a preset dictionary
+    if cmf & 0x0f != 8 || (u16::from(cmf) << 8 | u16::from(flg)) % 31 != 0 || flg & 0x20 != 0 {
+        return None;
+    }
+    inflate(&data[2..])
+}
+
+/// Decompresses raw deflate data.
+/// https://www.rfc-editor.org/rfc/rfc1951
+pub fn inflate(data: &[u8]) -> Option<Vec<u8>> {
+    let mut bits = Bits { data, pos: 0 };
+    let mut out = Vec::new();
+
+    loop {
+        let last = bits.read(1)? == 1;
+        match bits.read(2)? {
+            0 => stored(&mut bits, &mut out)?,
+            1 => {
+                let (literals, distances) = fixed_codes();
+                codes(&mut bits, &mut out, &literals, &distances)?;
+            }
+            2 => {
+                let (literals, distances) = dynamic_codes(&mut bits)?;
+                codes(&mut bits, &mut out, &literals, &distances)?;
+            }
+            _ => return None,
+        }
+        if last {
+            return Some(out);
+        }
+    }
+}
+
+/// the bits of the input, least significant first
+struct Bits<'a> {
+    data: &'a [u8],
+    /// position in bits
+    pos: usize,
+}
+
+impl Bits<'_> {
+    fn read(&mut self, count: u8) -> Option<u32> {
+        let mut value = 0;
+        for i in 0..count {
+            let byte = self.data.get(self.pos / 8)?;
+            value |= u32::from(byte >> (self.pos % 8) & 1) << i;
+            self.pos += 1;
+        }
+        Some(value)
+    }
+}
+
+/// A canonical Huffman code: the number of codes of each length, and the symbols ordered by code.
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    /// the code giving `lengths[symbol]` bits to each symbol, 0 leaving it out
+    fn new(lengths: &[u8]) -> Option<Self> {
+        let mut counts = [0u16; 16];
+        for &len in lengths {
+            counts[usize::from(len)] += 1;
+        }
+        counts[0] = 0;
+
+        // more codes than the lengths allow
+        let mut left: i32 = 1;
+        for &count in &counts[1..] {
+            left = (left << 1) - i32::from(count);
+            if left < 0 {
+                return None;
+            }
+        }
+
+        let mut symbols = Vec::with_capacity(lengths.len());
+        for len in 1..16 {
+            symbols.extend((0..lengths.len() as u16).filter(|&symbol| lengths[usize::from(symbol)] == len));
+        }
+        Some(Self { counts, symbols })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Option<u16> {
+        // the codes of a length follow those of the shorter ones
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for &count in &self.counts[1..] {
+            code |= bits.read(1)? as i32;
+            let count = i32::from(count);
+            if code - first < count {
+                return self.symbols.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+/// copies a block stored without compression
+fn stored(bits: &mut Bits, out: &mut Vec<u8>) -> Option<()> {
+    let start = bits.pos.div_ceil(8);
+    let header = bits.data.get(start..start + 4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) {
+        return None;
+    }
+    let end = start + 4 + usize::from(len);
+    out.extend_from_slice(bits.data.get(start + 4..end)?);
+    bits.pos = end * 8;
+    Some(())
+}
+
+/// the codes of blocks compressed with fixed codes
+fn fixed_codes() -> (Huffman, Huffman) {
+    let mut lengths = [8u8; 288];
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    let literals = Huffman::new(&lengths).expect("fixed literal code");
+    let distances = Huffman::new(&[5; 30]).expect("fixed distance code");
+    (literals, distances)
+}
+
+/// reads the codes a block compressed with dynamic codes starts with
+fn dynamic_codes(bits: &mut Bits) -> Option<(Huffman, Huffman)> {
+    let literal_count = bits.read(5)? as usize + 257;
+    let distance_count = bits.read(5)? as usize + 1;
+    let length_count = bits.read(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for &symbol in &CODE_LENGTH_ORDER[..length_count] {
+        code_lengths[symbol] = bits.read(3)? as u8;
+    }
+    let code_lengths = Huffman::new(&code_lengths)?;
+
+    // the lengths of both codes follow each other, a repetition may span them
+    let mut lengths = Vec::with_capacity(literal_count + distance_count);
+    while lengths.len() < literal_count + distance_count {
+        let (len, repeat) = match code_lengths.decode(bits)? {
+            len @ 0..16 => (len as u8, 1),
+            16 => (*lengths.last()?, 3 + bits.read(2)?),
+            17 => (0, 3 + bits.read(3)?),
+            _ => (0, 11 + bits.read(7)?),
+        };
+        lengths.extend(std::iter::repeat_n(len, repeat as usize));
+    }
+    if lengths.len() > literal_count + distance_count || lengths[256] == 0 {
+        return None;
+    }
+
+    let literals = Huffman::new(&lengths[..literal_count])?;
+    let distances = Huffman::new(&lengths[literal_count..])?;
+    Some((literals, distances))
+}
+
+/// decodes the literals and back references of a compressed block, up to its end
+fn codes(bits: &mut Bits, out: &mut Vec<u8>, literals: &Huffman, distances: &Huffman) -> Option<()> {
+    loop {
+        let symbol = usize::from(literals.decode(bits)?);
+        match symbol {
+            0..256 => out.push(symbol as u8),
+            256 => return Some(()),
+            _ => {
+                let code = symbol - 257;
+                let len = usize::from(*LENGTH_BASE.get(code)?) + bits.read(*LENGTH_EXTRA.get(code)?)? as usize;
+                let code = usize::from(distances.decode(bits)?);
+                let distance =
+                    usize::from(*DISTANCE_BASE.get(code)?) + bits.read(*DISTANCE_EXTRA.get(code)?)? as usize;
+
+                let start = out.len().checked_sub(distance)?;
+                // the copy may overlap what it produces
+                for i in 0..len {
+                    out.push(out[start + i]);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stored_block() {
+        // zlib.compress(b"hello", 0)
+        let data = [0x78, 0x01, 0x01, 0x05, 0x00, 0xfa, 0xff, b'h', b'e', b'l', b'l', b'o', 0x06, 0x2c, 0x02, 0x15];
+        assert_eq!(zlib(&data).unwrap(), b"hello");
+    }
+
+    #[test]
+    fn fixed_codes_with_references() {
+        // zlib.compress(b"hello hello hello hello")
+        let data = [
+            0x78, 0x9c, 0xcb, 0x48, 0xcd, 0xc9, 0xc9, 0x57, 0xc8, 0x40, 0x27, 0x01, 0x68, 0x03, 0x08, 0xb1,
+        ];
+        assert_eq!(zlib(&data).unwrap(), b"hello hello hello hello");
+    }
+
+    #[test]
+    fn dynamic_codes_of_a_commit() {
+        // zlib.compress(commit, 9)
+        let data = [
+            0x78, 0xda, 0x95, 0x8b, 0x4b, 0x0a, 0x02, 0x31, 0x10, 0x44, 0xf7, 0x39, 0x45, 0xef, 0x05, 0xc9,
+            0xc4, 0x4e, 0x26, 0x01, 0x11, 0xbd, 0x83, 0x1e, 0xa0, 0x3b, 0xe9, 0xa0, 0xe2, 0x7c, 0x88, 0x3d,
+            0xe0, 0xf1, 0x75, 0xc0, 0xad, 0x0b, 0x6b, 0x51, 0xbc, 0x7a, 0x50, 0xda, 0x44, 0x00, 0x39, 0x3a,
+            0x5f, 0x72, 0x40, 0x97, 0x39, 0x08, 0x27, 0xb2, 0xc1, 0x8a, 0x47, 0xae, 0xb1, 0x84, 0xe4, 0x62,
+            0xac, 0x2c, 0x82, 0xc9, 0xa2, 0x99, 0xa9, 0xc9, 0xa8, 0x60, 0x3b, 0xb7, 0x43, 0x1f, 0xfa, 0x98,
+            0x88, 0x73, 0x91, 0xfa, 0x6b, 0x1b, 0x5a, 0xf4, 0x3a, 0x35, 0x38, 0xc1, 0x05, 0xce, 0x2b, 0xec,
+            0xe9, 0x28, 0x2f, 0x1a, 0xe6, 0x87, 0x6c, 0xf3, 0x34, 0x1c, 0xa0, 0xeb, 0xed, 0x37, 0xb0, 0x59,
+            0xdb, 0x7c, 0xec, 0x70, 0x53, 0x95, 0x7f, 0x3e, 0xe6, 0xb9, 0xf0, 0x5d, 0xb2, 0x9a, 0x37, 0x7b,
+            0x33, 0x3a, 0x12,
+        ];
+        let commit = zlib(&data).unwrap();
+        assert!(commit.starts_with(b"tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\nparent "));
+        assert!(commit.ends_with(b"+0000\n\nsubject\n"));
+        assert_eq!(commit.len(), 204);
+    }
+
+    #[test]
+    fn rejected() {
+        assert!(zlib(&[0x78, 0x9d, 0x03, 0x00]).is_none());
+        // cut short
+        assert!(zlib(&[0x78, 0x9c, 0xcb, 0x48, 0xcd]).is_none());
+    }
+}
@@ -1 +1,8 @@
 pub mod error;
+pub mod escape;
+pub mod git;
+pub mod inflate;
+pub mod pattern;
+pub mod process;
+pub mod quote;
+pub mod regex;
@@ -0,0 +1,199 @@
+use std::fs;
+
+/// characters with a meaning in patterns
+const SPECIAL: &str = "*?[\\";
+
+/// Whether `text` matches the shell pattern `pattern`: `*` matches any string, `?` any character,
+/// `[...]` one of the characters listed, and a backslash quotes the next character.
+/// https://pubs.opengroup.org/onlinepubs/9699919799/utilities/V3_chap02.html#tag_18_13
+pub fn matches(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // where to resume after the last `*`, should the rest fail to match
+    let mut backtrack = None;
+
+    while t < text.len() {
+        let step = match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+                continue;
+            }
+            Some('?') => Some(1),
+            Some('[') => match bracket(&pattern[p..], text[t]) {
+                Some((true, len)) => Some(len),
+                Some((false, _)) => None,
+                // an unclosed bracket is a plain character
+                None => (text[t] == '[').then_some(1),
+            },
+            Some('\\') if p + 1 < pattern.len() => (pattern[p + 1] == text[t]).then_some(2),
+            Some(&ch) => (ch == text[t]).then_some(1),
+            None => None,
+        };
+
+        match step {
+            Some(len) => {
+                p += len;
+                t += 1;
+            }
+            None => match backtrack {
+                Some((star, start)) => {
+                    p = star + 1;
+                    t = start + 1;
+                    backtrack = Some((star, start + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&ch| ch == '*')
+}
+
+/// Matches `ch` against the bracket expression starting `pattern`,
+/// returns whether it matched and the length of the expression, `None` when it isn't closed.
+fn bracket(pattern: &[char], ch: char) -> Option<(bool, usize)> {
+    let mut i = 1;
+    let negated = matches!(pattern.get(i), Some('!' | '^'));
+    if negated {
+        i += 1;
+    }
+
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        let &c = pattern.get(i)?;
+        if c == ']' && !first {
+            break;
+        }
+        first = false;
+
+        if c == '['
+            && pattern.get(i + 1) == Some(&':')
+            && let Some(end) = (i + 2..pattern.len()).find(|&j| pattern[j] == ':' && pattern.get(j + 1) == Some(&']'))
+        {
+            let class: String = pattern[i + 2..end].iter().collect();
+            matched |= in_class(&class, ch);
+            i = end + 2;
+            continue;
+        }
+
+        let c = if c == '\\' {
+            i += 1;
+            *pattern.get(i)?
+        } else {
+            c
+        };
+
+        if pattern.get(i + 1) == Some(&'-') && pattern.get(i + 2).is_some_and(|&end| end != ']') {
+            matched |= (c..=pattern[i + 2]).contains(&ch);
+            i += 3;
+        } else {
+            matched |= c == ch;
+            i += 1;
+        }
+    }
+
+    Some((matched != negated, i + 1))
+}
+
+/// whether `ch` belongs to the character class `[:class:]`
+pub fn in_class(class: &str, ch: char) -> bool {
+    match class {
+        "alpha" => ch.is_alphabetic(),
+        "digit" => ch.is_ascii_digit(),
+        "alnum" => ch.is_alphanumeric(),
+        "upper" => ch.is_uppercase(),
+        "lower" => ch.is_lowercase(),
+        "space" => ch.is_whitespace(),
+        "blank" => ch == ' ' || ch == '\t',
+        "punct" => ch.is_ascii_punctuation(),
+        "xdigit" => ch.is_ascii_hexdigit(),
+        "cntrl" => ch.is_control(),
+        "print" => !ch.is_control(),
+        "graph" => !ch.is_control() && !ch.is_whitespace(),
+        _ => false,
+    }
+}
+
+/// whether `pattern` has an unquoted `*`, `?` or `[`
+pub fn has_wildcards(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// quotes the characters of `text` meaning something in a pattern
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if SPECIAL.contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// removes the backslashes quoting characters in `pattern`
+fn unescape(pattern: &str) -> String {
+    let mut text = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => text.extend(chars.next()),
+            _ => text.push(ch),
+        }
+    }
+    text
+}
+
+/// Pathname expansion: the sorted paths matching `pattern`, empty when none does.
+/// Files starting with a `.` are only matched by a pattern starting with one.
+pub fn glob(pattern: &str) -> Vec<String> {
+    let (mut paths, rest) = match pattern.strip_prefix('/') {
+        Some(rest) => (vec![String::from("/")], rest),
+        None => (vec![String::new()], pattern),
+    };
+
+    let components: Vec<&str> = rest.split('/').collect();
+    for (i, component) in components.iter().enumerate() {
+        let separator = if i + 1 < components.len() { "/" } else { "" };
+        let mut next = Vec::new();
+
+        for base in &paths {
+            if !has_wildcards(component) {
+                next.push(format!("{base}{}{separator}", unescape(component)));
+                continue;
+            }
+
+            let dir = if base.is_empty() { "." } else { base.as_str() };
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            let mut names: Vec<String> = entries
+                .flatten()
+                .filter_map(|entry| entry.file_name().into_string().ok())
+                .filter(|name| !name.starts_with('.') || component.starts_with('.') || component.starts_with("\\."))
+                .filter(|name| matches(component, name))
+                .collect();
+            names.sort();
+            next.extend(names.into_iter().map(|name| format!("{base}{name}{separator}")));
+        }
+
+        paths = next;
+    }
+
+    paths.retain(|path| fs::symlink_metadata(path).is_ok());
+    paths
+}
@@ -0,0 +1,31 @@
+use libc::{WEXITSTATUS, WIFEXITED, WIFSIGNALED, WTERMSIG, c_int, pid_t, waitpid};
+
+/// waits for `pid` to terminate and returns its exit status,
+/// processes killed by a signal are reported as `128 + signal` like other shells do.
+pub fn wait_status(pid: pid_t) -> i32 {
+    let mut status: c_int = 0;
+    loop {
+        let wid = unsafe { waitpid(pid, &mut status, 0) };
+        if wid == pid {
+            break;
+        }
+        // retry if interrupted by a signal
+        if wid == -1 && std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+            continue;
+        }
+        return 1;
+    }
+
+    status_code(status)
+}
+
+/// converts a raw status as reported by `waitpid` into a shell exit status
+pub fn status_code(status: c_int) -> i32 {
+    if WIFEXITED(status) {
+        WEXITSTATUS(status)
+    } else if WIFSIGNALED(status) {
+        128 + WTERMSIG(status)
+    } else {
+        1
+    }
+}
@@ -0,0 +1,9 @@
+/// `word` as written to be read back by the shell: in single quotes when it has characters
+/// the shell would interpret, as is otherwise.
+pub fn quote(word: &str) -> String {
+    let plain = !word.is_empty() && word.chars().all(|ch| ch.is_ascii_alphanumeric() || "_-./:,+=@%".contains(ch));
+    match plain {
+        true => word.to_string(),
+        false => format!("'{}'", word.replace('\'', "'\\''")),
+    }
+}
@@ -0,0 +1,161 @@
+use libc::{REG_EXTENDED, c_char, regcomp, regerror, regex_t, regexec, regfree, regmatch_t};
+use std::{ffi::CString, mem::MaybeUninit};
+
+/// characters with a meaning in extended regular expressions
+const SPECIAL: &str = "\\.[]()*+?{}|^$";
+
+/// An extended regular expression, as used by `=~` in `[[ ... ]]`, compiled by the C library:
+/// the leftmost match is used, the longest one at that position.
+/// https://pubs.opengroup.org/onlinepubs/9699919799/basedefs/V1_chap09.html#tag_09_04
+pub struct Regex {
+    regex: regex_t,
+    /// number of parenthesized groups
+    groups: usize,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Regex, String> {
+        let source = CString::new(pattern).map_err(|_| "the pattern has a NUL byte".to_string())?;
+        let mut regex = MaybeUninit::<regex_t>::uninit();
+        let code = unsafe { regcomp(regex.as_mut_ptr(), source.as_ptr(), REG_EXTENDED) };
+        if code != 0 {
+            let mut message = [0 as c_char; 128];
+            unsafe { regerror(code, regex.as_ptr(), message.as_mut_ptr(), message.len()) };
+            let message = unsafe { std::ffi::CStr::from_ptr(message.as_ptr()) };
+            return Err(message.to_string_lossy().into_owned());
+        }
+
+        Ok(Regex {
+            regex: unsafe { regex.assume_init() },
+            groups: groups(pattern),
+        })
+    }
+
+    /// The text matched in `text` followed by the one of each group, empty for a group that didn't
+    /// take part in the match, `None` when nothing matches.
+    pub fn captures(&self, text: &str) -> Option<Vec<String>> {
+        let text = CString::new(text).ok()?;
+        let mut matches = vec![regmatch_t { rm_so: -1, rm_eo: -1 }; self.groups + 1];
+        let code = unsafe { regexec(&self.regex, text.as_ptr(), matches.len(), matches.as_mut_ptr(), 0) };
+        if code != 0 {
+            return None;
+        }
+
+        let bytes = text.as_bytes();
+        let texts = matches
+            .iter()
+            .map(|m| match (usize::try_from(m.rm_so), usize::try_from(m.rm_eo)) {
+                (Ok(start), Ok(end)) => String::from_utf8_lossy(&bytes[start..end]).into_owned(),
+                _ => String::new(),
+            })
+            .collect();
+        Some(texts)
+    }
+}
+
+impl Drop for Regex {
+    fn drop(&mut self) {
+        unsafe { regfree(&mut self.regex) };
+    }
+}
+
+/// quotes the characters of `text` meaning something in a regular expression
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if SPECIAL.contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// the number of parenthesized groups of a valid pattern, those escaped or in brackets aside
+fn groups(pattern: &str) -> usize {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut count = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 1,
+            '(' => count += 1,
+            '[' => {
+                i += 1;
+                if chars.get(i) == Some(&'^') {
+                    i += 1;
+                }
+                // a `]` first is part of the list
+                if chars.get(i) == Some(&']') {
+                    i += 1;
+                }
+                while i < chars.len() && chars[i] != ']' {
+                    // `[:alpha:]`, `[=a=]` and `[.a.]` end with their own bracket
+                    if chars[i] == '['
+                        && let Some(&kind @ (':' | '=' | '.')) = chars.get(i + 1)
+                        && let Some(len) = chars[i + 2..].windows(2).position(|w| w == [kind, ']'])
+                    {
+                        i += len + 3;
+                    }
+                    i += 1;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn matched(pattern: &str, text: &str) -> Option<Vec<String>> {
+        Regex::new(pattern).unwrap().captures(text)
+    }
+
+    #[test]
+    fn leftmost_longest() {
+        assert_eq!(matched("a|ab", "xab").unwrap()[0], "ab");
+        assert_eq!(matched("(a|ab)(c|bcd)", "abcd").unwrap()[0], "abcd");
+        assert_eq!(matched("b+", "abbbcbb").unwrap()[0], "bbb");
+    }
+
+    #[test]
+    fn captures() {
+        assert_eq!(matched("^([a-z]+)-([0-9]+)$", "abc-123").unwrap(), ["abc-123", "abc", "123"]);
+        // a group left out of the match is empty
+        assert_eq!(matched("(x)?y", "y").unwrap(), ["y", ""]);
+        assert_eq!(matched("^[[:digit:]]+$", "12a"), None);
+    }
+
+    #[test]
+    fn groups_counted() {
+        assert_eq!(groups("(a)(b(c))"), 3);
+        assert_eq!(groups("\\(a[(]"), 0);
+        assert_eq!(groups("[]()][[:alpha:]](x)"), 1);
+        assert_eq!(matched("[(]\\((a)", "((a").unwrap(), ["((a", "a"]);
+    }
+
+    #[test]
+    fn invalid() {
+        assert!(Regex::new("a(").is_err());
+        assert!(Regex::new("[a").is_err());
+    }
+
+    #[test]
+    fn escaped() {
+        let regex = Regex::new(&escape("a.b*(c)")).unwrap();
+        assert!(regex.captures("a.b*(c)").is_some());
+        assert!(regex.captures("axbbc").is_none());
+    }
+
+    #[test]
+    fn nested_repetitions_fail_quickly() {
+        let start = Instant::now();
+        assert_eq!(matched("(a*)*b", &"a".repeat(26)), None);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -0,0 +1,52 @@
+use std::{
+    env, fs,
+    process::{Command, Stdio},
+};
+
+/// Runs `script` with `args` as positional parameters, returns what it wrote to its standard output.
+fn run(name: &str, script: &str, args: &[&str]) -> String {
+    let path = env::temp_dir().join(format!("0-shell-{name}-{}", std::process::id()));
+    fs::write(&path, script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_shell"))
+        .arg("--norc")
+        .arg(&path)
+        .args(args)
+        .env("HISTFILE", "")
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    let _ = fs::remove_file(path);
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn quoted_at_is_a_word_per_parameter() {
+    let script = "count() { echo \"$# [$1] [$2] [$3]\"; }\ncount \"$@\"\ncount \"<$@>\"\nshift 3\ncount \"$@\"\n";
+    let out = run("at", script, &["a b", "", "c"]);
+    assert_eq!(out, "3 [a b] [] [c]\n3 [<a b] [] [c>]\n0 [] [] []\n");
+}
+
+#[test]
+fn quoted_at_without_parameters() {
+    let script = "count() { echo $#; }\ncount \"$@\"\ncount \"$@\" \"$@\"\ncount \"x$@\"\ncount \"\"\n";
+    assert_eq!(run("empty-at", script, &[]), "0\n0\n1\n1\n");
+}
+
+#[test]
+fn getopts_options_and_arguments() {
+    let script = "getopts ab:c opt; echo \"$? $opt [$OPTARG] $OPTIND\"\n".repeat(5);
+    let out = run("getopts", &script, &["-ac", "-bvalue", "-b", "x", "--", "-c"]);
+    assert_eq!(out, "0 a [] 1\n0 c [] 2\n0 b [value] 3\n0 b [x] 5\n1 ? [] 6\n");
+}
+
+#[test]
+fn getopts_silent_errors() {
+    let script = "getopts :ab: opt; echo \"$? $opt [$OPTARG]\"\n".repeat(3);
+    assert_eq!(run("getopts-silent", &script, &["-x", "-a", "-b"]), "0 ? [x]\n0 a []\n0 : [b]\n");
+}
+
+#[test]
+fn assignments_only_written_as_such() {
+    let script = "a=\"x=1\"\n$a\n\"q=2\"\necho \"[$x] [$q]\"\nb=$a c=\"two words\" d=*\necho \"$b|$c|$d\"\n";
+    assert_eq!(run("assignments", script, &[]), "[] []\nx=1|two words|*\n");
+}
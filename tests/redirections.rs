@@ -0,0 +1,81 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// an empty directory of its own for a test to run the shell in
+fn scratch(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("0-shell-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs `script` in `dir`, returns what it wrote to its standard output.
+fn run(dir: &Path, script: &str) -> String {
+    fs::write(dir.join("script"), script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_shell"))
+        .args(["--norc", "script"])
+        .current_dir(dir)
+        .env("HISTFILE", "")
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn contents(dir: &Path, file: &str) -> String {
+    fs::read_to_string(dir.join(file)).unwrap_or_default()
+}
+
+#[test]
+fn builtin_output_goes_to_file() {
+    let dir = scratch("builtin");
+    let out = run(&dir, "echo hi > a\ntype cd > b\neval 'echo evaluated' > c\necho after\n");
+    assert_eq!(out, "after\n");
+    assert_eq!(contents(&dir, "a"), "hi\n");
+    assert_eq!(contents(&dir, "b"), "cd is a shell builtin\n");
+    assert_eq!(contents(&dir, "c"), "evaluated\n");
+}
+
+#[test]
+fn builtin_input_comes_from_file() {
+    let dir = scratch("input");
+    fs::write(dir.join("in"), "one\ntwo\n").unwrap();
+    let out = run(&dir, "read first < in\necho \"$first\"\nread second\necho \"[$second]\"\n");
+    assert_eq!(out, "one\n[]\n");
+}
+
+#[test]
+fn builtin_output_copied_to_every_file() {
+    let dir = scratch("multios");
+    run(&dir, "echo both > a > b\n");
+    assert_eq!(contents(&dir, "a"), "both\n");
+    assert_eq!(contents(&dir, "b"), "both\n");
+}
+
+#[test]
+fn exec_redirects_the_shell() {
+    let dir = scratch("exec");
+    let out = run(&dir, "exec 3> three\nsh -c 'echo numbered >&3'\nexec > out\necho first\n/bin/echo second\necho third\n");
+    assert_eq!(out, "");
+    assert_eq!(contents(&dir, "three"), "numbered\n");
+    assert_eq!(contents(&dir, "out"), "first\nsecond\nthird\n");
+}
+
+#[test]
+fn function_redirected_as_a_whole() {
+    let dir = scratch("function");
+    fs::write(dir.join("in"), "line\n").unwrap();
+    let out = run(&dir, "f() { echo start; read x; echo \"got $x\"; }\nf > out < in\necho done\n");
+    assert_eq!(out, "done\n");
+    assert_eq!(contents(&dir, "out"), "start\ngot line\n");
+}
+
+#[test]
+fn read_times_out_on_endless_input() {
+    let dir = scratch("timeout");
+    let out = run(&dir, "read -t 1 x < /dev/zero\necho $?\n");
+    assert_eq!(out, "142\n");
+}
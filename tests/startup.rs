@@ -0,0 +1,46 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+/// an empty directory of its own for a test to use as `HOME`
+fn scratch(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("0-shell-{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Runs `script` as a login shell with `home` as `HOME`, returns what it wrote to its standard output.
+fn run_login(home: &Path, args: &[&str], script: &str) -> String {
+    fs::write(home.join("script"), script).unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_shell"))
+        .arg("-l")
+        .args(args)
+        .arg("script")
+        .current_dir(home)
+        .env("HOME", home)
+        .env("HISTFILE", "")
+        .stdin(Stdio::null())
+        .output()
+        .unwrap();
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+#[test]
+fn logout_file_only_run_by_the_login_shell() {
+    let home = scratch("logout");
+    fs::write(home.join(".0shell_logout"), "echo logout\n").unwrap();
+    let out = run_login(&home, &["--noprofile"], "x=$(exit 0)\necho \"x=[$x]\"\n");
+    assert_eq!(out, "x=[]\nlogout\n");
+}
+
+#[test]
+fn profiles_of_the_shell_only() {
+    let home = scratch("profile");
+    fs::write(home.join(".0shell_profile"), "echo own profile\n").unwrap();
+    fs::write(home.join(".profile"), "echo sh profile\n").unwrap();
+    assert_eq!(run_login(&home, &[], "echo script\n"), "own profile\nscript\n");
+    assert_eq!(run_login(&home, &["--noprofile"], "echo script\n"), "script\n");
+}